@@ -0,0 +1,93 @@
+use super::{Sample, SampleFormat};
+use crate::{Error, Result};
+use av_codec::decoder::Decoder;
+use av_data::frame::{ArcFrame, FrameBufferConv};
+use av_data::packet::Packet;
+
+struct State {
+    frame: ArcFrame,
+    format: SampleFormat,
+    /// Whether each channel is stored in its own plane rather than interleaved in plane 0.
+    planar: bool,
+    channels: usize,
+    samples: usize,
+    i: usize,
+}
+
+fn invalid_frame(err: impl std::fmt::Debug) -> Error {
+    Error::InvalidAudio(format!("{:?}", err))
+}
+
+/// Decodes packets with an rust-av decoder and yields the interleaved samples of the last
+/// decoded frame, interleaving planar frames.
+pub struct FrameDecoder {
+    decoder: Box<dyn Decoder>,
+    state: Option<State>,
+}
+
+impl FrameDecoder {
+    pub fn new(mut decoder: Box<dyn Decoder>, extradata: Option<&[u8]>) -> Result<Self> {
+        if let Some(extradata) = extradata {
+            decoder.set_extradata(extradata);
+        }
+        decoder.configure()?;
+        Ok(Self {
+            decoder,
+            state: None,
+        })
+    }
+
+    pub fn send_packet(&mut self, packet: &Packet) -> Result<()> {
+        self.decoder.send_packet(packet)?;
+        let frame = self.decoder.receive_frame()?;
+        let info = frame.kind.get_audio_info().unwrap();
+        let soniton = &*info.format;
+        let format = match (soniton.float, soniton.signed, soniton.bits) {
+            _ if soniton.be => None,
+            (false, false, 8) => Some(SampleFormat::U8),
+            (false, true, 16) => Some(SampleFormat::S16),
+            (false, true, 32) => Some(SampleFormat::S32),
+            (true, _, 32) => Some(SampleFormat::F32),
+            (true, _, 64) => Some(SampleFormat::F64),
+            _ => None,
+        }
+        .ok_or_else(|| Error::UnsupportedSampleFormat(format!("{:?}", soniton)))?;
+        let channels = info.map.len();
+        self.state = Some(State {
+            format,
+            planar: soniton.planar,
+            channels,
+            samples: info.samples * channels,
+            i: 0,
+            frame,
+        });
+        Ok(())
+    }
+
+    /// Returns `None` once the samples of the last frame are exhausted.
+    pub fn next_sample(&mut self) -> Result<Option<Sample>> {
+        let Some(state) = self.state.as_mut() else {
+            return Ok(None);
+        };
+        if state.i == state.samples {
+            self.state = None;
+            return Ok(None);
+        }
+        let (plane, index) = if state.planar {
+            (state.i % state.channels, state.i / state.channels)
+        } else {
+            (0, state.i)
+        };
+        let buf = &state.frame.buf;
+        let sample = match state.format {
+            SampleFormat::U8 => Sample::U8(buf.as_slice(plane).map_err(invalid_frame)?[index]),
+            SampleFormat::S16 => Sample::S16(buf.as_slice(plane).map_err(invalid_frame)?[index]),
+            SampleFormat::S32 => Sample::S32(buf.as_slice(plane).map_err(invalid_frame)?[index]),
+            SampleFormat::F32 => Sample::F32(buf.as_slice(plane).map_err(invalid_frame)?[index]),
+            SampleFormat::F64 => Sample::F64(buf.as_slice(plane).map_err(invalid_frame)?[index]),
+            format => return Err(Error::UnsupportedSampleFormat(format!("{:?}", format))),
+        };
+        state.i += 1;
+        Ok(Some(sample))
+    }
+}
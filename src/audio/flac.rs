@@ -0,0 +1,75 @@
+use super::{AudioStream, Sample};
+use crate::{Error, Result};
+use claxon::{Block, FlacReader};
+use std::io::Read;
+
+pub struct FlacContext<R: Read> {
+    reader: FlacReader<R>,
+    block: Option<Block>,
+    i: usize,
+}
+
+impl<R: Read> FlacContext<R> {
+    pub fn from_reader(reader: R) -> Result<Self> {
+        let reader = FlacReader::new(reader)?;
+        let bits = reader.streaminfo().bits_per_sample;
+        if bits > 32 {
+            return Err(Error::UnsupportedSampleFormat(format!("{} bit flac", bits)));
+        }
+        Ok(Self {
+            reader,
+            block: None,
+            i: 0,
+        })
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample>> {
+        loop {
+            if let Some(block) = self.block.as_ref() {
+                let channels = block.channels() as usize;
+                if self.i < block.duration() as usize * channels {
+                    let sample =
+                        block.sample((self.i % channels) as u32, (self.i / channels) as u32);
+                    self.i += 1;
+                    let bits = self.reader.streaminfo().bits_per_sample;
+                    return Ok(Some(match bits {
+                        16 => Sample::S16(sample as i16),
+                        _ => Sample::S32(sample << (32 - bits)),
+                    }));
+                }
+            }
+            let buffer = self
+                .block
+                .take()
+                .map(Block::into_buffer)
+                .unwrap_or_default();
+            self.i = 0;
+            match self.reader.blocks().read_next_or_eof(buffer)? {
+                Some(block) => self.block = Some(block),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for FlacContext<R> {
+    type Item = Result<Sample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sample().transpose()
+    }
+}
+
+impl<R: Read> AudioStream for FlacContext<R> {
+    fn sample_rate(&self) -> usize {
+        self.reader.streaminfo().sample_rate as _
+    }
+
+    fn duration(&self) -> usize {
+        self.reader.streaminfo().samples.unwrap_or_default() as _
+    }
+
+    fn channels(&self) -> usize {
+        self.reader.streaminfo().channels as _
+    }
+}
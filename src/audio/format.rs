@@ -0,0 +1,72 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Matroska,
+    Ogg,
+    Flac,
+    Mp3,
+    Mp4,
+}
+
+impl AudioFormat {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        Some(match ext.to_ascii_lowercase().as_str() {
+            "wav" | "wave" => Self::Wav,
+            "weba" | "webm" | "mka" | "mkv" => Self::Matroska,
+            "ogg" | "oga" | "opus" => Self::Ogg,
+            "flac" => Self::Flac,
+            "mp3" => Self::Mp3,
+            "mp4" | "m4a" => Self::Mp4,
+            _ => return None,
+        })
+    }
+
+    /// Recognizes the container from the first bytes of a file.
+    pub fn from_magic(header: &[u8]) -> Option<Self> {
+        match header {
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Self::Wav),
+            [0x1a, 0x45, 0xdf, 0xa3, ..] => Some(Self::Matroska),
+            [b'O', b'g', b'g', b'S', ..] => Some(Self::Ogg),
+            [b'f', b'L', b'a', b'C', ..] => Some(Self::Flac),
+            [b'I', b'D', b'3', ..] => Some(Self::Mp3),
+            // MPEG audio frame sync, excluding the reserved layer used by ADTS
+            [0xff, b, ..] if b & 0xe0 == 0xe0 && b & 0x06 != 0 => Some(Self::Mp3),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(Self::Mp4),
+            _ => None,
+        }
+    }
+
+    /// Sniffs the container from the start of a reader, leaving its position unchanged.
+    pub fn detect<R: Read + Seek>(reader: &mut R) -> Result<Option<Self>> {
+        let position = reader.stream_position()?;
+        let mut header = [0; 12];
+        let mut len = 0;
+        while len < header.len() {
+            match reader.read(&mut header[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        reader.seek(SeekFrom::Start(position))?;
+        Ok(Self::from_magic(&header[..len]))
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Wav => "wav",
+            Self::Matroska => "matroska",
+            Self::Ogg => "ogg",
+            Self::Flac => "flac",
+            Self::Mp3 => "mp3",
+            Self::Mp4 => "mp4",
+        })
+    }
+}
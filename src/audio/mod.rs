@@ -1,34 +1,73 @@
+use self::flac::FlacContext;
+use self::mpeg::MpegContext;
+use self::ogg::OggContext;
+use self::raw::RawContext;
 use self::wav::WavContext;
 use self::webm::WebmContext;
-use anyhow::{Context, Result};
+use crate::{Error, Result};
 use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
 use std::path::Path;
 
+mod codec;
+mod flac;
+mod format;
+mod g711;
+mod mpeg;
+mod ogg;
+mod raw;
 mod wav;
 mod webm;
 
+pub use self::format::AudioFormat;
+pub use self::raw::{RawEncoding, RawFormat};
+
 #[derive(Clone, Copy, Debug)]
 pub enum SampleFormat {
+    U8,
+    S8,
     S16,
+    S24,
     S32,
     F32,
+    F64,
+    MuLaw,
+    ALaw,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum Sample {
+    U8(u8),
+    S8(i8),
     S16(i16),
+    /// 24 bit sample in the low bits.
+    S24(i32),
     S32(i32),
     F32(f32),
+    F64(f64),
+    /// G.711 mu-law encoded sample.
+    MuLaw(u8),
+    /// G.711 A-law encoded sample.
+    ALaw(u8),
 }
 
 impl Sample {
     pub fn to_f32(self) -> f32 {
+        const S24_MAX: i32 = (1 << 23) - 1;
         match self {
+            Self::U8(sample) => (sample as f32 - 128.0) / i8::MAX as f32,
+            Self::S8(sample) => sample as f32 / i8::MAX as f32,
             Self::S16(sample) => sample as f32 / i16::MAX as f32,
+            Self::S24(sample) => sample as f32 / S24_MAX as f32,
             Self::S32(sample) => sample as f32 / i32::MAX as f32,
             Self::F32(sample) => sample,
+            Self::F64(sample) => sample as f32,
+            Self::MuLaw(sample) => g711::mulaw_to_i16(sample) as f32 / i16::MAX as f32,
+            Self::ALaw(sample) => g711::alaw_to_i16(sample) as f32 / i16::MAX as f32,
         }
     }
 }
@@ -39,6 +78,15 @@ impl From<Sample> for f32 {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AudioInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<AudioFormat>,
+    pub sample_rate: usize,
+    pub channels: usize,
+    pub duration: f32,
+}
+
 pub trait AudioStream: Iterator<Item = Result<Sample>> {
     fn sample_rate(&self) -> usize;
     fn duration(&self) -> usize;
@@ -52,7 +100,9 @@ fn average_channels(stream: impl AudioStream) -> Result<Vec<f32>> {
     while let Some(res) = stream.next() {
         let mut sample = res?;
         for _ in 0..(channels - 1) {
-            sample += stream.next().context("invalid number of samples")??;
+            sample += stream
+                .next()
+                .ok_or_else(|| Error::InvalidAudio("invalid number of samples".into()))??;
         }
         sample /= channels as f32;
         samples.push(sample);
@@ -82,29 +132,108 @@ fn resample(sample_rate: usize, target_sample_rate: usize, samples: Vec<f32>) ->
         .unwrap())
 }
 
-fn read_audio_stream(stream: impl AudioStream, target_sample_rate: usize) -> Result<Vec<f32>> {
+fn read_audio_stream(
+    stream: impl AudioStream,
+    format: Option<AudioFormat>,
+    target_sample_rate: usize,
+) -> Result<(AudioInfo, Vec<f32>)> {
     let sample_rate = stream.sample_rate();
+    let channels = stream.channels();
     let samples = average_channels(stream)?;
+    let info = AudioInfo {
+        format,
+        sample_rate,
+        channels,
+        duration: samples.len() as f32 / sample_rate as f32,
+    };
     if sample_rate == target_sample_rate {
-        return Ok(samples);
+        return Ok((info, samples));
+    }
+    Ok((info, resample(sample_rate, target_sample_rate, samples)?))
+}
+
+/// Reads audio in the given container, or in the one detected from its contents if `None`.
+pub fn read_audio_from_reader<R: Read + Seek>(
+    mut reader: R,
+    format: Option<AudioFormat>,
+    target_sample_rate: usize,
+) -> Result<(AudioInfo, Vec<f32>)> {
+    let format = match format {
+        Some(format) => format,
+        None => AudioFormat::detect(&mut reader)?.ok_or(Error::UndetectedFormat)?,
+    };
+    match format {
+        AudioFormat::Wav => read_audio_stream(
+            WavContext::from_reader(reader)?,
+            Some(format),
+            target_sample_rate,
+        ),
+        AudioFormat::Matroska => read_audio_stream(
+            WebmContext::from_reader(reader)?,
+            Some(format),
+            target_sample_rate,
+        ),
+        AudioFormat::Ogg => read_audio_stream(
+            OggContext::from_reader(reader)?,
+            Some(format),
+            target_sample_rate,
+        ),
+        AudioFormat::Flac => read_audio_stream(
+            FlacContext::from_reader(reader)?,
+            Some(format),
+            target_sample_rate,
+        ),
+        AudioFormat::Mp3 => {
+            read_audio_stream(MpegContext::mp3(reader)?, Some(format), target_sample_rate)
+        }
+        AudioFormat::Mp4 => {
+            read_audio_stream(MpegContext::mp4(reader)?, Some(format), target_sample_rate)
+        }
     }
-    resample(sample_rate, target_sample_rate, samples)
 }
 
-pub fn read_audio(path: &Path, target_sample_rate: usize) -> Result<Vec<f32>> {
-    let ext = path
-        .extension()
-        .context("missing extension")?
-        .to_str()
-        .context("invalid extension")?;
-    match ext {
-        "wav" => read_audio_stream(WavContext::from_path(path)?, target_sample_rate),
-        "weba" | "webm" => read_audio_stream(WebmContext::from_path(path)?, target_sample_rate),
-        _ => anyhow::bail!("unsupported extension {}", ext),
+pub fn read_audio(path: &Path, target_sample_rate: usize) -> Result<(AudioInfo, Vec<f32>)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let format = match AudioFormat::detect(&mut reader)? {
+        Some(format) => format,
+        None => {
+            let ext = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .ok_or_else(|| Error::UnknownFormat(path.to_path_buf()))?;
+            AudioFormat::from_extension(ext)
+                .ok_or_else(|| Error::UnsupportedExtension(ext.to_string()))?
+        }
+    };
+    log::debug!("reading {:?} as {}", path, format);
+    read_audio_from_reader(reader, Some(format), target_sample_rate)
+}
+
+/// Reads headerless audio, the returned info has no format.
+pub fn read_raw_audio<R: Read>(
+    reader: R,
+    format: RawFormat,
+    target_sample_rate: usize,
+) -> Result<(AudioInfo, Vec<f32>)> {
+    read_audio_stream(RawContext::new(reader, format), None, target_sample_rate)
+}
+
+/// Reads a file as headerless audio if its format is given, sniffing the container otherwise.
+pub(crate) fn read_audio_file(
+    path: &Path,
+    raw_format: Option<RawFormat>,
+    target_sample_rate: usize,
+) -> Result<(AudioInfo, Vec<f32>)> {
+    match raw_format {
+        Some(format) => {
+            let reader = BufReader::new(File::open(path)?);
+            read_raw_audio(reader, format, target_sample_rate)
+        }
+        None => read_audio(path, target_sample_rate),
     }
 }
 
 pub fn transcode_audio(input: &Path, output: &Path, target_sample_rate: usize) -> Result<()> {
-    let samples = read_audio(input, target_sample_rate)?;
+    let (_, samples) = read_audio(input, target_sample_rate)?;
     self::wav::write_wav(output, &samples, target_sample_rate)
 }
@@ -0,0 +1,167 @@
+use super::{AudioStream, Sample};
+use crate::{Error, Result};
+use std::io::{Cursor, Read};
+use symphonia::core::audio::{SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::default::formats::{IsoMp4Reader, MpaReader};
+
+/// Returns the length of the ID3v2 tags at the start of `data`, the MP3 demuxer expects the
+/// stream to start with a frame.
+fn id3_len(data: &[u8]) -> usize {
+    let mut len = 0;
+    while let Some(header) = data.get(len..len + 10) {
+        if &header[..3] != b"ID3" {
+            break;
+        }
+        let size = header[6..10]
+            .iter()
+            .fold(0, |size, &b| (size << 7) | (b & 0x7f) as usize);
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        len += 10 + size + footer;
+    }
+    len.min(data.len())
+}
+
+/// Decodes MP3 and MP4 audio with symphonia. The input is read into memory because symphonia
+/// needs a `Send + Sync` source.
+pub struct MpegContext {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track: u32,
+    buffer: Option<SampleBuffer<f32>>,
+    sample_rate: usize,
+    channels: usize,
+    duration: usize,
+    i: usize,
+}
+
+impl MpegContext {
+    pub fn mp3(mut reader: impl Read) -> Result<Self> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data)?;
+        data.drain(..id3_len(&data));
+        let source = MediaSourceStream::new(Box::new(Cursor::new(data)), Default::default());
+        let format = MpaReader::try_new(source, &FormatOptions::default())?;
+        Self::new(Box::new(format))
+    }
+
+    pub fn mp4(mut reader: impl Read) -> Result<Self> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data)?;
+        let source = MediaSourceStream::new(Box::new(Cursor::new(data)), Default::default());
+        let format = IsoMp4Reader::try_new(source, &FormatOptions::default())?;
+        Self::new(Box::new(format))
+    }
+
+    fn new(format: Box<dyn FormatReader>) -> Result<Self> {
+        let (track, decoder) = format
+            .tracks()
+            .iter()
+            .find_map(|track| {
+                if track.codec_params.codec == CODEC_TYPE_NULL {
+                    log::info!("skipping non audio stream");
+                    return None;
+                }
+                let options = DecoderOptions::default();
+                match symphonia::default::get_codecs().make(&track.codec_params, &options) {
+                    Ok(decoder) => Some((track.id, decoder)),
+                    Err(err) => {
+                        log::info!("skipping audio stream: {}", err);
+                        None
+                    }
+                }
+            })
+            .ok_or(Error::NoAudioStream)?;
+        let duration = decoder.codec_params().n_frames.unwrap_or_default() as usize;
+        let mut context = Self {
+            format,
+            decoder,
+            track,
+            buffer: None,
+            sample_rate: 0,
+            channels: 0,
+            duration,
+            i: 0,
+        };
+        // AAC in MP4 may only signal the channel layout in the bitstream, so take the signal
+        // spec from the first decoded packet
+        let spec = context.decode_packet()?.ok_or(Error::NoAudioStream)?;
+        context.sample_rate = spec.rate as usize;
+        context.channels = spec.channels.count();
+        Ok(context)
+    }
+
+    /// Decodes the next packet of the track, returns `None` at the end of the stream.
+    fn decode_packet(&mut self) -> Result<Option<SignalSpec>> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(err))
+                    if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(None)
+                }
+                Err(err) => return Err(err.into()),
+            };
+            if packet.track_id() != self.track {
+                continue;
+            }
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphoniaError::DecodeError(err)) => {
+                    log::warn!("skipping corrupt packet: {}", err);
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            };
+            let spec = *decoded.spec();
+            if self.buffer.is_some() && spec.channels.count() != self.channels {
+                return Err(Error::InvalidAudio("number of channels changed".into()));
+            }
+            self.buffer
+                .get_or_insert_with(|| SampleBuffer::new(decoded.capacity() as u64, spec))
+                .copy_interleaved_ref(decoded);
+            self.i = 0;
+            return Ok(Some(spec));
+        }
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample>> {
+        loop {
+            if let Some(buffer) = self.buffer.as_ref() {
+                if let Some(&sample) = buffer.samples().get(self.i) {
+                    self.i += 1;
+                    return Ok(Some(Sample::F32(sample)));
+                }
+            }
+            if self.decode_packet()?.is_none() {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+impl Iterator for MpegContext {
+    type Item = Result<Sample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sample().transpose()
+    }
+}
+
+impl AudioStream for MpegContext {
+    fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    fn duration(&self) -> usize {
+        self.duration
+    }
+
+    fn channels(&self) -> usize {
+        self.channels
+    }
+}
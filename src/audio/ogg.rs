@@ -0,0 +1,137 @@
+use super::codec::FrameDecoder;
+use super::{AudioStream, Sample};
+use crate::error::ensure;
+use crate::{Error, Result};
+use av_codec::decoder::{Decoder, Descriptor};
+use av_data::packet::Packet;
+use av_vorbis::decoder::VORBIS_DESCR;
+use libopus::decoder::OPUS_DESCR;
+use ogg::reading::PacketReader;
+use std::io::{Read, Seek};
+
+fn next_packet<R: Read + Seek>(
+    reader: &mut PacketReader<R>,
+    serial: u32,
+) -> Result<Option<ogg::Packet>> {
+    while let Some(packet) = reader.read_packet()? {
+        if packet.stream_serial() == serial {
+            return Ok(Some(packet));
+        }
+    }
+    Ok(None)
+}
+
+fn header_packet<R: Read + Seek>(reader: &mut PacketReader<R>, serial: u32) -> Result<Vec<u8>> {
+    next_packet(reader, serial)?
+        .map(|packet| packet.data)
+        .ok_or_else(|| Error::InvalidAudio("missing ogg header packet".into()))
+}
+
+/// Packs the Vorbis header packets the way Matroska stores them as codec private data,
+/// which is what the Vorbis decoder expects as extradata.
+fn xiph_lace(packets: &[Vec<u8>]) -> Vec<u8> {
+    let mut extradata = vec![packets.len() as u8 - 1];
+    for packet in &packets[..packets.len() - 1] {
+        extradata.extend(std::iter::repeat(255).take(packet.len() / 255));
+        extradata.push((packet.len() % 255) as u8);
+    }
+    for packet in packets {
+        extradata.extend_from_slice(packet);
+    }
+    extradata
+}
+
+pub struct OggContext<R: Read + Seek> {
+    reader: PacketReader<R>,
+    decoder: FrameDecoder,
+    serial: u32,
+    sample_rate: usize,
+    channels: usize,
+}
+
+impl<R: Read + Seek> OggContext<R> {
+    pub fn from_reader(reader: R) -> Result<Self> {
+        let mut reader = PacketReader::new(reader);
+        loop {
+            let packet = reader.read_packet()?.ok_or(Error::NoAudioStream)?;
+            if !packet.first_in_stream() {
+                continue;
+            }
+            let serial = packet.stream_serial();
+            let head = packet.data;
+            if head.starts_with(b"OpusHead") && head.len() >= 19 {
+                let channels = head[9] as usize;
+                ensure!(
+                    channels > 0,
+                    Error::InvalidAudio("opus stream without channels".into())
+                );
+                // the comment header carries no decoding parameters
+                header_packet(&mut reader, serial)?;
+                let decoder = Box::new(OPUS_DESCR.create()) as Box<dyn Decoder>;
+                return Ok(Self {
+                    decoder: FrameDecoder::new(decoder, Some(&head))?,
+                    reader,
+                    serial,
+                    sample_rate: 48000,
+                    channels,
+                });
+            }
+            if head.starts_with(b"\x01vorbis") && head.len() >= 30 {
+                let sample_rate = u32::from_le_bytes(head[12..16].try_into().unwrap());
+                let channels = head[11] as usize;
+                ensure!(
+                    channels > 0 && sample_rate > 0,
+                    Error::InvalidAudio("vorbis stream without channels or sample rate".into())
+                );
+                let comment = header_packet(&mut reader, serial)?;
+                let setup = header_packet(&mut reader, serial)?;
+                let extradata = xiph_lace(&[head, comment, setup]);
+                let decoder = Box::new(VORBIS_DESCR.create()) as Box<dyn Decoder>;
+                return Ok(Self {
+                    decoder: FrameDecoder::new(decoder, Some(&extradata))?,
+                    reader,
+                    serial,
+                    sample_rate: sample_rate as usize,
+                    channels,
+                });
+            }
+            log::info!("skipping ogg stream with unsupported codec");
+        }
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample>> {
+        loop {
+            if let Some(sample) = self.decoder.next_sample()? {
+                return Ok(Some(sample));
+            }
+            let Some(data) = next_packet(&mut self.reader, self.serial)? else {
+                return Ok(None);
+            };
+            let mut packet = Packet::with_capacity(0);
+            packet.data = data.data;
+            self.decoder.send_packet(&packet)?;
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for OggContext<R> {
+    type Item = Result<Sample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sample().transpose()
+    }
+}
+
+impl<R: Read + Seek> AudioStream for OggContext<R> {
+    fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    fn duration(&self) -> usize {
+        0
+    }
+
+    fn channels(&self) -> usize {
+        self.channels
+    }
+}
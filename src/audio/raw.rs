@@ -0,0 +1,145 @@
+use super::{AudioStream, Sample};
+use crate::{Error, Result};
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::str::FromStr;
+
+/// Sample encoding of headerless audio.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RawEncoding {
+    S16Le,
+    F32Le,
+    MuLaw,
+    ALaw,
+}
+
+impl RawEncoding {
+    fn bytes_per_sample(self) -> usize {
+        match self {
+            Self::S16Le => 2,
+            Self::F32Le => 4,
+            Self::MuLaw | Self::ALaw => 1,
+        }
+    }
+}
+
+impl FromStr for RawEncoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "s16le" => Self::S16Le,
+            "f32le" => Self::F32Le,
+            "mulaw" | "ulaw" => Self::MuLaw,
+            "alaw" => Self::ALaw,
+            _ => return Err(Error::InvalidRawFormat(s.to_string())),
+        })
+    }
+}
+
+impl fmt::Display for RawEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::S16Le => "s16le",
+            Self::F32Le => "f32le",
+            Self::MuLaw => "mulaw",
+            Self::ALaw => "alaw",
+        })
+    }
+}
+
+/// Layout of headerless audio, parsed from `encoding:sample_rate:channels`, e.g.
+/// `s16le:8000:1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawFormat {
+    pub encoding: RawEncoding,
+    pub sample_rate: usize,
+    pub channels: usize,
+}
+
+impl FromStr for RawFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidRawFormat(s.to_string());
+        let mut parts = s.split(':');
+        let (Some(encoding), Some(sample_rate), Some(channels), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let format = Self {
+            encoding: encoding.parse()?,
+            sample_rate: sample_rate.parse().map_err(|_| invalid())?,
+            channels: channels.parse().map_err(|_| invalid())?,
+        };
+        if format.sample_rate == 0 || format.channels == 0 {
+            return Err(invalid());
+        }
+        Ok(format)
+    }
+}
+
+impl fmt::Display for RawFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.encoding, self.sample_rate, self.channels
+        )
+    }
+}
+
+pub struct RawContext<R: Read> {
+    reader: R,
+    format: RawFormat,
+}
+
+impl<R: Read> RawContext<R> {
+    pub fn new(reader: R, format: RawFormat) -> Self {
+        Self { reader, format }
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample>> {
+        let mut bytes = [0; 4];
+        let bytes = &mut bytes[..self.format.encoding.bytes_per_sample()];
+        let mut len = 0;
+        while len < bytes.len() {
+            match self.reader.read(&mut bytes[len..]) {
+                Ok(0) if len == 0 => return Ok(None),
+                Ok(0) => return Err(Error::InvalidAudio("truncated raw sample".into())),
+                Ok(n) => len += n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(Some(match self.format.encoding {
+            RawEncoding::S16Le => Sample::S16(i16::from_le_bytes([bytes[0], bytes[1]])),
+            RawEncoding::F32Le => Sample::F32(f32::from_le_bytes(bytes.try_into().unwrap())),
+            RawEncoding::MuLaw => Sample::MuLaw(bytes[0]),
+            RawEncoding::ALaw => Sample::ALaw(bytes[0]),
+        }))
+    }
+}
+
+impl<R: Read> Iterator for RawContext<R> {
+    type Item = Result<Sample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sample().transpose()
+    }
+}
+
+impl<R: Read> AudioStream for RawContext<R> {
+    fn sample_rate(&self) -> usize {
+        self.format.sample_rate
+    }
+
+    fn duration(&self) -> usize {
+        0
+    }
+
+    fn channels(&self) -> usize {
+        self.format.channels
+    }
+}
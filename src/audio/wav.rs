@@ -1,58 +1,188 @@
-use super::{AudioStream, Sample};
-use anyhow::Result;
-use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
-use std::fs::File;
-use std::io::BufReader;
+use super::{AudioStream, Sample, SampleFormat};
+use crate::error::ensure;
+use crate::{Error, Result};
+use hound::{WavReader, WavSpec, WavWriter};
+use std::io::{Chain, Cursor, Read, Take};
 use std::path::Path;
 
-pub struct WavContext(WavReader<BufReader<File>>);
+const WAVE_FORMAT_ALAW: u16 = 6;
+const WAVE_FORMAT_MULAW: u16 = 7;
 
-impl WavContext {
-    pub fn from_path(path: &Path) -> Result<Self> {
-        Ok(Self(WavReader::new(BufReader::new(File::open(path)?))?))
+struct G711Header {
+    format: SampleFormat,
+    sample_rate: usize,
+    channels: usize,
+    len: u64,
+}
+
+/// Reads `len` bytes and appends them to `header`. The bytes are read as they come rather than
+/// into a buffer of `len`, which comes from the file.
+fn read_bytes(reader: &mut impl Read, len: u64, header: &mut Vec<u8>) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    reader.by_ref().take(len).read_to_end(&mut bytes)?;
+    ensure!(
+        bytes.len() as u64 == len,
+        Error::InvalidAudio("truncated wav header".into())
+    );
+    header.extend_from_slice(&bytes);
+    Ok(bytes)
+}
+
+/// Reads the chunks up to the data of a G.711 file, which hound doesn't support. Returns `None`
+/// for other files, with all bytes read so far in `header` so hound can parse them again.
+fn read_g711_header(reader: &mut impl Read, header: &mut Vec<u8>) -> Result<Option<G711Header>> {
+    let riff = read_bytes(reader, 12, header)?;
+    if &riff[..4] != b"RIFF" || &riff[8..] != b"WAVE" {
+        return Ok(None);
+    }
+    let mut fmt = None;
+    loop {
+        let chunk = read_bytes(reader, 8, header)?;
+        let len = u32::from_le_bytes(chunk[4..].try_into().unwrap()) as u64;
+        match &chunk[..4] {
+            b"fmt " => {
+                let bytes = read_bytes(reader, len + len % 2, header)?;
+                if bytes.len() < 16 {
+                    return Err(Error::InvalidAudio("wav fmt chunk too short".into()));
+                }
+                let format = match u16::from_le_bytes([bytes[0], bytes[1]]) {
+                    WAVE_FORMAT_MULAW => SampleFormat::MuLaw,
+                    WAVE_FORMAT_ALAW => SampleFormat::ALaw,
+                    _ => return Ok(None),
+                };
+                let channels = u16::from_le_bytes([bytes[2], bytes[3]]) as usize;
+                let sample_rate = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+                ensure!(
+                    channels > 0 && sample_rate > 0,
+                    Error::InvalidAudio("wav without channels or sample rate".into())
+                );
+                fmt = Some((format, sample_rate, channels));
+            }
+            b"data" => {
+                let (format, sample_rate, channels) =
+                    fmt.ok_or_else(|| Error::InvalidAudio("wav data before fmt chunk".into()))?;
+                return Ok(Some(G711Header {
+                    format,
+                    sample_rate,
+                    channels,
+                    len,
+                }));
+            }
+            _ => {
+                let len = len + len % 2;
+                let copied = std::io::copy(&mut reader.by_ref().take(len), header)?;
+                ensure!(
+                    copied == len,
+                    Error::InvalidAudio("truncated wav chunk".into())
+                );
+            }
+        }
+    }
+}
+
+enum WavStream<R: Read> {
+    Pcm(WavReader<Chain<Cursor<Vec<u8>>, R>>),
+    G711 {
+        data: Take<R>,
+        sample_rate: usize,
+        channels: usize,
+    },
+}
+
+pub struct WavContext<R: Read> {
+    stream: WavStream<R>,
+    format: SampleFormat,
+}
+
+impl<R: Read> WavContext<R> {
+    pub fn from_reader(mut reader: R) -> Result<Self> {
+        let mut header = vec![];
+        if let Some(g711) = read_g711_header(&mut reader, &mut header)? {
+            return Ok(Self {
+                stream: WavStream::G711 {
+                    data: reader.take(g711.len),
+                    sample_rate: g711.sample_rate,
+                    channels: g711.channels,
+                },
+                format: g711.format,
+            });
+        }
+        let reader = WavReader::new(Cursor::new(header).chain(reader))?;
+        let spec = reader.spec();
+        let format = match (spec.sample_format, spec.bits_per_sample) {
+            (hound::SampleFormat::Int, 8) => SampleFormat::S8,
+            (hound::SampleFormat::Int, 16) => SampleFormat::S16,
+            (hound::SampleFormat::Int, 24) => SampleFormat::S24,
+            (hound::SampleFormat::Int, 32) => SampleFormat::S32,
+            (hound::SampleFormat::Float, 32) => SampleFormat::F32,
+            (format, bits) => {
+                return Err(Error::UnsupportedSampleFormat(format!(
+                    "{:?} with {} bits",
+                    format, bits
+                )))
+            }
+        };
+        Ok(Self {
+            stream: WavStream::Pcm(reader),
+            format,
+        })
     }
 }
 
-impl AudioStream for WavContext {
+impl<R: Read> AudioStream for WavContext<R> {
     fn sample_rate(&self) -> usize {
-        self.0.spec().sample_rate as _
+        match &self.stream {
+            WavStream::Pcm(reader) => reader.spec().sample_rate as _,
+            WavStream::G711 { sample_rate, .. } => *sample_rate,
+        }
     }
 
     fn duration(&self) -> usize {
-        self.0.duration() as _
+        match &self.stream {
+            WavStream::Pcm(reader) => reader.duration() as _,
+            WavStream::G711 { data, channels, .. } => data.limit() as usize / (*channels).max(1),
+        }
     }
 
     fn channels(&self) -> usize {
-        self.0.spec().channels as _
+        match &self.stream {
+            WavStream::Pcm(reader) => reader.spec().channels as _,
+            WavStream::G711 { channels, .. } => *channels,
+        }
     }
 }
 
-impl Iterator for WavContext {
+impl<R: Read> Iterator for WavContext<R> {
     type Item = Result<Sample>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let spec = self.0.spec();
-        Some(match (spec.sample_format, spec.bits_per_sample) {
-            (SampleFormat::Int, 16) => self
-                .0
-                .samples()
-                .next()?
-                .map(Sample::S16)
-                .map_err(Into::into),
-            (SampleFormat::Int, 32) => self
-                .0
-                .samples()
-                .next()?
-                .map(Sample::S32)
-                .map_err(Into::into),
-            (SampleFormat::Float, 32) => self
-                .0
-                .samples()
-                .next()?
-                .map(Sample::F32)
-                .map_err(Into::into),
-            _ => Err(anyhow::anyhow!("unsupported sample format")),
-        })
+        let reader = match &mut self.stream {
+            WavStream::Pcm(reader) => reader,
+            WavStream::G711 { data, .. } => {
+                let mut byte = [0];
+                return match data.read(&mut byte) {
+                    Ok(0) => None,
+                    Ok(_) if matches!(self.format, SampleFormat::ALaw) => {
+                        Some(Ok(Sample::ALaw(byte[0])))
+                    }
+                    Ok(_) => Some(Ok(Sample::MuLaw(byte[0]))),
+                    Err(err) => Some(Err(err.into())),
+                };
+            }
+        };
+        Some(
+            match self.format {
+                SampleFormat::S8 => reader.samples().next()?.map(Sample::S8),
+                SampleFormat::S16 => reader.samples().next()?.map(Sample::S16),
+                SampleFormat::S24 => reader.samples().next()?.map(Sample::S24),
+                SampleFormat::S32 => reader.samples().next()?.map(Sample::S32),
+                SampleFormat::F32 => reader.samples().next()?.map(Sample::F32),
+                format => {
+                    return Some(Err(Error::UnsupportedSampleFormat(format!("{:?}", format))))
+                }
+            }
+            .map_err(Into::into),
+        )
     }
 }
 
@@ -61,7 +191,7 @@ pub fn write_wav(path: &Path, samples: &[f32], sample_rate: usize) -> Result<()>
         channels: 1,
         sample_rate: sample_rate as _,
         bits_per_sample: 32,
-        sample_format: SampleFormat::Float,
+        sample_format: hound::SampleFormat::Float,
     };
     let mut writer = WavWriter::create(path, spec)?;
     for sample in samples {
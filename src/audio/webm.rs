@@ -1,52 +1,28 @@
-use super::{AudioStream, Sample, SampleFormat};
-use anyhow::{Context as _, Result};
+use super::codec::FrameDecoder;
+use super::{AudioStream, Sample};
+use crate::{Error, Result};
 use av_codec::decoder::{Decoder, Descriptor};
-use av_data::audiosample::formats;
-use av_data::frame::{ArcFrame, FrameBufferConv};
 use av_data::params::{AudioInfo, MediaKind};
 use av_format::buffer::AccReader;
 use av_format::demuxer::{Context, Event};
 use av_vorbis::decoder::VORBIS_DESCR;
 use libopus::decoder::OPUS_DESCR;
 use matroska::demuxer::MkvDemuxer;
-use std::fs::File;
-use std::path::Path;
+use std::io::{Read, Seek};
 
-struct State {
-    frame: ArcFrame,
-    format: SampleFormat,
-    samples: usize,
-    i: usize,
-}
-
-impl State {
-    fn new(frame: ArcFrame, format: SampleFormat, samples: usize) -> Self {
-        Self {
-            frame,
-            format,
-            samples,
-            i: 0,
-        }
-    }
-}
-
-pub struct WebmContext {
-    demuxer: Context<MkvDemuxer, AccReader<File>>,
-    decoder: Box<dyn Decoder>,
+pub struct WebmContext<R: Read + Seek> {
+    demuxer: Context<MkvDemuxer, AccReader<R>>,
+    decoder: FrameDecoder,
     info: AudioInfo,
     stream_index: isize,
-    state: Option<State>,
 }
 
-impl WebmContext {
-    pub fn from_path(path: &Path) -> Result<Self> {
-        let file = File::open(path)?;
-        let reader = AccReader::with_capacity(4 * 1024, file);
+impl<R: Read + Seek> WebmContext<R> {
+    pub fn from_reader(reader: R) -> Result<Self> {
+        let reader = AccReader::with_capacity(4 * 1024, reader);
         let mut demuxer = Context::new(MkvDemuxer::new(), reader);
-        demuxer
-            .read_headers()
-            .context("Cannot parse the format headers")?;
-        let (info, mut decoder, stream_index) = demuxer
+        demuxer.read_headers()?;
+        let (info, decoder, stream_index) = demuxer
             .info
             .streams
             .iter()
@@ -55,7 +31,7 @@ impl WebmContext {
                     log::info!("skipping non audio stream");
                     return None;
                 };
-                let mut decoder = match stream.params.codec_id.as_ref() {
+                let decoder = match stream.params.codec_id.as_ref() {
                     Some(codec_id) if OPUS_DESCR.describe().codec == codec_id => {
                         Box::new(OPUS_DESCR.create()) as Box<dyn Decoder>
                     }
@@ -74,53 +50,28 @@ impl WebmContext {
                         return None;
                     }
                 };
-                if let Some(ref extra_data) = stream.params.extradata {
-                    decoder.set_extradata(extra_data);
-                }
+                let decoder = FrameDecoder::new(decoder, stream.params.extradata.as_deref());
                 Some((info.clone(), decoder, stream.index as _))
             })
-            .context("no supported audio stream found")?;
-        decoder.configure().context("Codec configure failed")?;
+            .ok_or(Error::NoAudioStream)?;
         Ok(Self {
             demuxer,
-            decoder,
+            decoder: decoder?,
             info,
             stream_index,
-            state: None,
         })
     }
 
     fn next_sample(&mut self) -> Result<Option<Sample>> {
         loop {
-            if let Some(state) = self.state.as_mut() {
-                if state.i < state.samples {
-                    let sample = match state.format {
-                        SampleFormat::S16 => Sample::S16(state.frame.buf.as_slice(0)?[state.i]),
-                        SampleFormat::F32 => Sample::F32(state.frame.buf.as_slice(0)?[state.i]),
-                        _ => anyhow::bail!("unsupported sample format {:?}", state.format),
-                    };
-                    state.i += 1;
-                    return Ok(Some(sample));
-                } else {
-                    self.state.take();
-                }
+            if let Some(sample) = self.decoder.next_sample()? {
+                return Ok(Some(sample));
             }
             match self.demuxer.read_event()? {
                 Event::NewPacket(packet) => {
-                    if packet.stream_index != self.stream_index {
-                        continue;
+                    if packet.stream_index == self.stream_index {
+                        self.decoder.send_packet(&packet)?;
                     }
-                    self.decoder.send_packet(&packet)?;
-                    let frame = self.decoder.receive_frame()?;
-                    let info = frame.kind.get_audio_info().unwrap();
-                    let format = match &*info.format {
-                        &formats::S16 => SampleFormat::S16,
-                        &formats::S32 => SampleFormat::S32,
-                        &formats::F32 => SampleFormat::F32,
-                        _ => anyhow::bail!("unsupported sample format {:?}", info.format),
-                    };
-                    let samples = info.samples * info.map.len();
-                    self.state = Some(State::new(frame, format, samples))
                 }
                 Event::Eof => return Ok(None),
                 _ => {}
@@ -129,7 +80,7 @@ impl WebmContext {
     }
 }
 
-impl Iterator for WebmContext {
+impl<R: Read + Seek> Iterator for WebmContext<R> {
     type Item = Result<Sample>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -137,7 +88,7 @@ impl Iterator for WebmContext {
     }
 }
 
-impl AudioStream for WebmContext {
+impl<R: Read + Seek> AudioStream for WebmContext<R> {
     fn sample_rate(&self) -> usize {
         self.info.rate
     }
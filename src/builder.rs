@@ -0,0 +1,234 @@
+use crate::decoder::Decoder;
+use crate::error::ensure;
+use crate::models::ModelManifest;
+use crate::{fingerprint, ModelInfo, Silero};
+use crate::{Error, Result};
+use ort::{
+    Environment, ExecutionProvider, GraphOptimizationLevel, Session, SessionBuilder, ValueType,
+};
+use std::path::Path;
+
+pub struct SileroBuilder {
+    batch_size: usize,
+    window_length: Option<f32>,
+    window_overlap: f32,
+    sample_rate: Option<usize>,
+    intra_threads: Option<usize>,
+    inter_threads: Option<usize>,
+    optimization_level: Option<GraphOptimizationLevel>,
+    execution_provider: ExecutionProvider,
+}
+
+impl Default for SileroBuilder {
+    fn default() -> Self {
+        Self {
+            batch_size: 10,
+            window_length: None,
+            window_overlap: 2.0,
+            sample_rate: None,
+            intra_threads: None,
+            inter_threads: None,
+            optimization_level: None,
+            execution_provider: ExecutionProvider::CPU(Default::default()),
+        }
+    }
+}
+
+impl SileroBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Length in seconds of the windows long audio is split into, the model's by default.
+    pub fn window_length(mut self, seconds: f32) -> Self {
+        self.window_length = Some(seconds);
+        self
+    }
+
+    /// Overlap in seconds between consecutive windows.
+    pub fn window_overlap(mut self, seconds: f32) -> Self {
+        self.window_overlap = seconds;
+        self
+    }
+
+    /// Sample rate the model expects, the model's by default.
+    pub fn sample_rate(mut self, sample_rate: usize) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    pub fn intra_threads(mut self, threads: usize) -> Self {
+        self.intra_threads = Some(threads);
+        self
+    }
+
+    pub fn inter_threads(mut self, threads: usize) -> Self {
+        self.inter_threads = Some(threads);
+        self
+    }
+
+    pub fn optimization_level(mut self, level: GraphOptimizationLevel) -> Self {
+        self.optimization_level = Some(level);
+        self
+    }
+
+    pub fn execution_provider(mut self, provider: ExecutionProvider) -> Self {
+        self.execution_provider = provider;
+        self
+    }
+
+    pub(crate) fn session(&mut self, model: &[u8]) -> Result<Session> {
+        let environment = Environment::builder()
+            .with_name("silero")
+            .with_execution_providers([self.execution_provider.clone()])
+            .build()?
+            .into_arc();
+        let mut builder = SessionBuilder::new(&environment)?;
+        if let Some(level) = self.optimization_level.take() {
+            builder = builder.with_optimization_level(level)?;
+        }
+        if let Some(threads) = self.intra_threads {
+            builder = builder.with_intra_threads(thread_count(threads)?)?;
+        }
+        if let Some(threads) = self.inter_threads {
+            builder = builder.with_inter_threads(thread_count(threads)?)?;
+        }
+        Ok(builder.with_model_from_memory(model)?)
+    }
+
+    pub fn build(mut self, model: &[u8], labels: &str) -> Result<Silero> {
+        ensure!(
+            self.batch_size > 0,
+            Error::InvalidConfig("batch size must be positive".into())
+        );
+        // without a manifest, the defaults are the English model's
+        let defaults = ModelManifest::default();
+        let sample_rate = self.sample_rate.unwrap_or(defaults.sample_rate);
+        let window_length = self.window_length.unwrap_or(defaults.window);
+        ensure!(
+            sample_rate > 0,
+            Error::InvalidConfig("sample rate must be positive".into())
+        );
+        let max_sequence_length = (window_length * sample_rate as f32).round() as usize;
+        let chunk_overlap = (self.window_overlap * sample_rate as f32).round() as usize;
+        ensure!(
+            chunk_overlap < max_sequence_length,
+            Error::InvalidConfig(format!(
+                "window overlap of {}s must be shorter than the window length of {}s",
+                self.window_overlap, window_length
+            ))
+        );
+        let session = self.session(model)?;
+        let (fixed_batch_size, fixed_sequence_length) = input_shape(&session)?;
+        if let Some(batch_size) = fixed_batch_size {
+            ensure!(
+                self.batch_size <= batch_size,
+                Error::InvalidConfig(format!(
+                    "batch size {} exceeds the model's batch size {}",
+                    self.batch_size, batch_size
+                ))
+            );
+        }
+        if let Some(sequence_length) = fixed_sequence_length {
+            ensure!(
+                max_sequence_length <= sequence_length,
+                Error::InvalidConfig(format!(
+                    "window of {} samples exceeds the model's input length {}",
+                    max_sequence_length, sequence_length
+                ))
+            );
+        }
+        Ok(Silero {
+            session,
+            decoder: Decoder::from_json(labels)?,
+            batch_size: self.batch_size,
+            max_sequence_length,
+            chunk_overlap,
+            sample_rate,
+            fixed_batch_size,
+            fixed_sequence_length,
+            vad: None,
+            raw_format: None,
+            model: ModelInfo {
+                name: "silero".into(),
+                language: None,
+                fingerprint: fingerprint(model),
+            },
+        })
+    }
+
+    pub fn build_from_path(self, model: &Path, labels: &Path) -> Result<Silero> {
+        let name = model
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned());
+        let mut silero = self.build(&std::fs::read(model)?, &std::fs::read_to_string(labels)?)?;
+        if let Some(name) = name {
+            silero.model.name = name;
+        }
+        Ok(silero)
+    }
+
+    /// Builds the model a manifest describes, taking its sample rate and window length unless
+    /// they are set on the builder.
+    pub fn build_from_manifest(mut self, dir: &Path, manifest: &ModelManifest) -> Result<Silero> {
+        self.sample_rate = self.sample_rate.or(Some(manifest.sample_rate));
+        self.window_length = self.window_length.or(Some(manifest.window));
+        let mut silero =
+            self.build_from_path(&dir.join(&manifest.model), &dir.join(&manifest.labels))?;
+        silero.model.name = manifest.name.clone();
+        silero.model.language = Some(manifest.language.clone());
+        Ok(silero)
+    }
+
+    /// Builds the English model from a directory such as one returned by
+    /// [`find_model_dir`](crate::find_model_dir).
+    pub fn build_from_dir(self, dir: &Path) -> Result<Silero> {
+        self.build_from_manifest(dir, &ModelManifest::default())
+    }
+
+    #[cfg(feature = "embedded-model")]
+    pub fn build_default(self) -> Result<Silero> {
+        let mut silero = self.build(crate::MODEL, crate::LABELS)?;
+        silero.model.name = "en_v5".into();
+        silero.model.language = Some("en".into());
+        Ok(silero)
+    }
+}
+
+/// Converts a thread count to the `i16` ort takes.
+fn thread_count(threads: usize) -> Result<i16> {
+    threads.try_into().map_err(|_| {
+        Error::InvalidConfig(format!(
+            "{} threads exceed the maximum of {}",
+            threads,
+            i16::MAX
+        ))
+    })
+}
+
+/// The batch size and sequence length the model declares for its input, `None` where the
+/// dimension is dynamic.
+fn input_shape(session: &Session) -> Result<(Option<usize>, Option<usize>)> {
+    let input = session
+        .inputs
+        .iter()
+        .find(|input| input.name == "input")
+        .ok_or_else(|| Error::InvalidModel("no input named \"input\"".into()))?;
+    let ValueType::Tensor { dimensions, .. } = &input.input_type else {
+        return Err(Error::InvalidModel("input is not a tensor".into()));
+    };
+    ensure!(
+        dimensions.len() == 2,
+        Error::InvalidModel(format!(
+            "expected an input of shape (batch, samples), got {:?}",
+            dimensions
+        ))
+    );
+    let fixed = |dimension: i64| (dimension > 0).then_some(dimension as usize);
+    Ok((fixed(dimensions[0]), fixed(dimensions[1])))
+}
@@ -1,7 +1,94 @@
-use anyhow::{Context, Result};
+use crate::hotwords::{Hotword, HotwordState, HotwordTrie};
+use crate::lm::LanguageModel;
+use crate::transcript::{Token, Transcript};
+use crate::{Error, Result};
+use ndarray::{Array2, ArrayView2, ArrayViewMut1};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
+use std::ops::Range;
 use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct BeamSearchOptions {
+    pub beam_width: usize,
+    pub n_best: usize,
+    pub lm: Option<Arc<LanguageModel>>,
+    pub lm_weight: f32,
+    pub word_bonus: f32,
+    pub hotwords: Vec<Hotword>,
+}
+
+impl Default for BeamSearchOptions {
+    fn default() -> Self {
+        Self {
+            beam_width: 16,
+            n_best: 1,
+            lm: None,
+            lm_weight: 0.5,
+            word_bonus: 1.0,
+            hotwords: vec![],
+        }
+    }
+}
+
+impl BeamSearchOptions {
+    fn word_score(&self, text: &str) -> f32 {
+        let Some(lm) = self.lm.as_ref() else {
+            return 0.0;
+        };
+        let mut words = vec!["<s>"];
+        words.extend(text.split_whitespace());
+        let (word, context) = words.split_last().unwrap();
+        self.lm_weight * lm.score(context, word) + self.word_bonus
+    }
+
+    fn end_score(&self, text: &str) -> f32 {
+        let Some(lm) = self.lm.as_ref() else {
+            return 0.0;
+        };
+        let mut score = 0.0;
+        if !text.ends_with(char::is_whitespace) && !text.is_empty() {
+            score += self.word_score(text);
+        }
+        let mut context = vec!["<s>"];
+        context.extend(text.split_whitespace());
+        score + self.lm_weight * lm.score(&context, "</s>")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hypothesis {
+    pub transcript: Transcript,
+    pub score: f32,
+}
+
+#[derive(Clone)]
+struct Beam<'a> {
+    spans: Vec<Range<usize>>,
+    score: f32,
+    best: f32,
+    text: String,
+    last_piece: Option<&'a str>,
+    fusion: f32,
+    hotword: HotwordState,
+}
+
+impl<'a> Beam<'a> {
+    fn total(&self) -> f32 {
+        self.score + self.fusion
+    }
+
+    fn merge(&mut self, other: Beam<'a>) {
+        if other.best > self.best {
+            self.spans = other.spans;
+            self.best = other.best;
+        }
+        self.score = log_sum_exp(self.score, other.score);
+    }
+}
 
 pub struct Decoder {
     labels: Vec<String>,
@@ -9,16 +96,20 @@ pub struct Decoder {
     two_idx: usize,
 }
 
+fn invalid_labels(err: serde_json::Error) -> Error {
+    Error::InvalidLabels(err.to_string())
+}
+
 impl Decoder {
     pub fn new(labels: Vec<String>) -> Result<Self> {
         let blank_idx = labels
             .iter()
             .position(|label| label == "_")
-            .context("invalid labels")?;
+            .ok_or_else(|| Error::InvalidLabels("missing blank label _".into()))?;
         let two_idx = labels
             .iter()
             .position(|label| label == "2")
-            .context("invalid labels")?;
+            .ok_or_else(|| Error::InvalidLabels("missing repeat label 2".into()))?;
         Ok(Self {
             labels,
             blank_idx,
@@ -27,14 +118,14 @@ impl Decoder {
     }
 
     pub fn from_json(json: &str) -> Result<Self> {
-        Self::new(serde_json::from_str(json)?)
+        Self::new(serde_json::from_str(json).map_err(invalid_labels)?)
     }
 
     #[allow(unused)]
     pub fn from_path(path: &Path) -> Result<Self> {
         let file = File::open(path)?;
         let reader = BufReader::new(file);
-        let labels: Vec<String> = serde_json::from_reader(reader)?;
+        let labels: Vec<String> = serde_json::from_reader(reader).map_err(invalid_labels)?;
         Self::new(labels)
     }
 
@@ -42,31 +133,253 @@ impl Decoder {
         &self.labels
     }
 
-    pub fn decode(&self, argm: &[usize]) -> Result<String> {
-        let mut pieces = vec![];
-        for i in argm.iter().copied() {
+    fn pieces(&self, argm: &[usize]) -> Vec<(&str, Vec<usize>)> {
+        let mut pieces: Vec<(&str, usize)> = vec![];
+        for (frame, i) in argm.iter().copied().enumerate() {
             if i == self.two_idx {
                 if pieces.is_empty() {
-                    pieces.push(" ");
+                    pieces.push((" ", frame));
                 } else {
-                    pieces.push("$");
-                    let last = pieces[pieces.len() - 2];
-                    pieces.push(last);
+                    pieces.push(("$", frame));
+                    let (last, _) = pieces[pieces.len() - 2];
+                    pieces.push((last, frame));
                 }
             } else if i != self.blank_idx {
-                pieces.push(&self.labels[i]);
+                pieces.push((&self.labels[i], frame));
             }
         }
-        let mut s = String::new();
+        let mut collapsed: Vec<(&str, Vec<usize>)> = vec![];
+        for (piece, frame) in pieces {
+            match collapsed.last_mut() {
+                Some((last, frames)) if *last == piece => frames.push(frame),
+                _ => collapsed.push((piece, vec![frame])),
+            }
+        }
+        collapsed
+    }
+
+    pub fn decode(&self, argm: &[usize]) -> Result<String> {
+        let s: String = self
+            .pieces(argm)
+            .into_iter()
+            .map(|(piece, _)| piece)
+            .collect();
+        Ok(s.replace('$', "").trim().to_string())
+    }
+
+    pub fn align(&self, argm: &[usize], probs: &[f32], frame_stride: f32) -> Transcript {
+        let tokens = self
+            .pieces(argm)
+            .into_iter()
+            .filter(|(piece, _)| *piece != "$")
+            .map(|(piece, frames)| Token {
+                text: piece.to_string(),
+                start: frames[0] as f32 * frame_stride,
+                end: (frames[frames.len() - 1] + 1) as f32 * frame_stride,
+                confidence: frames.iter().map(|frame| probs[*frame]).sum::<f32>()
+                    / frames.len() as f32,
+            })
+            .collect();
+        Transcript::new(tokens, argm.len() as f32 * frame_stride)
+    }
+
+    pub fn greedy(&self, probs: ArrayView2<f32>, frame_stride: f32) -> Transcript {
+        let (argm, probs): (Vec<usize>, Vec<f32>) = probs
+            .rows()
+            .into_iter()
+            .map(|row| {
+                row.iter()
+                    .copied()
+                    .enumerate()
+                    .reduce(|(ia, a), (ib, b)| if a >= b { (ia, a) } else { (ib, b) })
+                    .unwrap()
+            })
+            .unzip();
+        self.align(&argm, &probs, frame_stride)
+    }
+
+    fn push_label<'a>(
+        &'a self,
+        beam: &mut Beam<'a>,
+        label: usize,
+        options: &BeamSearchOptions,
+        hotwords: &HotwordTrie,
+    ) {
+        let word_start = beam.text.is_empty() || beam.text.ends_with(char::is_whitespace);
+        let piece = if label == self.two_idx {
+            beam.last_piece.unwrap_or(" ")
+        } else {
+            self.labels[label].as_str()
+        };
+        if !hotwords.is_empty() {
+            let space = piece.trim().is_empty();
+            beam.fusion += hotwords.advance(&mut beam.hotword, label, word_start, space);
+        }
+        if label != self.two_idx && beam.last_piece == Some(piece) {
+            return;
+        }
+        if piece.trim().is_empty() && !word_start {
+            beam.fusion += options.word_score(&beam.text);
+        }
+        beam.text.push_str(piece);
+        beam.last_piece = Some(piece);
+    }
+
+    pub fn tokenize(&self, text: &str) -> Option<Vec<usize>> {
+        let text = text.to_lowercase();
+        let mut labels: Vec<usize> = vec![];
         let mut last = None;
-        for piece in pieces {
-            let curr = Some(piece);
-            if curr == last {
+        let mut rest = text.as_str();
+        while !rest.is_empty() {
+            let (label, len) = self
+                .labels
+                .iter()
+                .enumerate()
+                .filter(|(i, label)| {
+                    *i != self.blank_idx && *i != self.two_idx && rest.starts_with(label.as_str())
+                })
+                .map(|(i, label)| (i, label.len()))
+                .filter(|(_, len)| *len > 0)
+                .max_by_key(|(_, len)| *len)?;
+            if last == Some(label) {
+                labels.push(self.two_idx);
+            } else {
+                labels.push(label);
+            }
+            last = Some(label);
+            rest = &rest[len..];
+        }
+        Some(labels)
+    }
+
+    fn hotwords(&self, hotwords: &[Hotword]) -> HotwordTrie {
+        HotwordTrie::new(hotwords.iter().filter_map(|hotword| {
+            let Some(labels) = self.tokenize(&hotword.phrase) else {
+                log::warn!(
+                    "skipping hotword {:?} that can't be tokenized",
+                    hotword.phrase
+                );
+                return None;
+            };
+            Some((labels, hotword.boost))
+        }))
+    }
+
+    pub fn beam_search(
+        &self,
+        probs: ArrayView2<f32>,
+        frame_stride: f32,
+        options: &BeamSearchOptions,
+    ) -> Vec<Hypothesis> {
+        let root = Beam {
+            spans: vec![],
+            score: 0.0,
+            best: 0.0,
+            text: String::new(),
+            last_piece: None,
+            fusion: 0.0,
+            hotword: HotwordState::default(),
+        };
+        let hotwords = self.hotwords(&options.hotwords);
+        let mut beams: Vec<(Vec<usize>, Beam)> = vec![(vec![], root)];
+        for (frame, row) in probs.rows().into_iter().enumerate() {
+            let mut candidates: Vec<usize> = (0..row.len()).collect();
+            candidates.sort_by(|a, b| row[*b].total_cmp(&row[*a]));
+            candidates.truncate(options.beam_width);
+            let mut next: HashMap<Vec<usize>, Beam> = HashMap::new();
+            for (labels, beam) in &beams {
+                for label in candidates.iter().copied() {
+                    let log_prob = row[label].ln();
+                    let mut labels = labels.clone();
+                    let mut beam = beam.clone();
+                    beam.score += log_prob;
+                    beam.best += log_prob;
+                    if label == self.blank_idx {
+                        // blanks never change the prefix
+                    } else if label != self.two_idx && labels.last() == Some(&label) {
+                        beam.spans.last_mut().unwrap().end = frame + 1;
+                    } else {
+                        labels.push(label);
+                        beam.spans.push(frame..frame + 1);
+                        self.push_label(&mut beam, label, options, &hotwords);
+                    }
+                    match next.entry(labels) {
+                        Entry::Occupied(mut entry) => entry.get_mut().merge(beam),
+                        Entry::Vacant(entry) => {
+                            entry.insert(beam);
+                        }
+                    }
+                }
+            }
+            beams = next.into_iter().collect();
+            beams.sort_by(|(_, a), (_, b)| b.total().total_cmp(&a.total()));
+            beams.truncate(options.beam_width);
+        }
+        for (_, beam) in &mut beams {
+            beam.fusion += options.end_score(&beam.text) + hotwords.finish(&beam.hotword);
+        }
+        beams.sort_by(|(_, a), (_, b)| b.total().total_cmp(&a.total()));
+        let mut hypotheses: Vec<Hypothesis> = Vec::with_capacity(options.n_best);
+        for (labels, beam) in beams {
+            if hypotheses.len() == options.n_best {
+                break;
+            }
+            let mut argm = vec![self.blank_idx; probs.nrows()];
+            let mut frame_probs = vec![0.0; probs.nrows()];
+            for (label, span) in labels.iter().copied().zip(&beam.spans) {
+                for frame in [span.start, span.end - 1] {
+                    argm[frame] = label;
+                    frame_probs[frame] = probs[[frame, label]];
+                }
+            }
+            let transcript = self.align(&argm, &frame_probs, frame_stride);
+            if hypotheses
+                .iter()
+                .any(|hypothesis| hypothesis.transcript.text == transcript.text)
+            {
                 continue;
             }
-            last = curr;
-            s.push_str(piece);
+            hypotheses.push(Hypothesis {
+                transcript,
+                score: beam.total(),
+            });
+        }
+        hypotheses
+    }
+}
+
+fn log_sum_exp(a: f32, b: f32) -> f32 {
+    let max = a.max(b);
+    if max == f32::NEG_INFINITY {
+        return max;
+    }
+    max + ((a - max).exp() + (b - max).exp()).ln()
+}
+
+fn softmax_row(mut row: ArrayViewMut1<f32>) {
+    let max = row.fold(f32::NEG_INFINITY, |max, x| max.max(*x));
+    row.mapv_inplace(|x| (x - max).exp());
+    let sum = row.sum();
+    row /= sum;
+}
+
+pub fn softmax(logits: ArrayView2<f32>) -> Array2<f32> {
+    let mut probs = logits.to_owned();
+    for row in probs.rows_mut() {
+        softmax_row(row);
+    }
+    probs
+}
+
+/// Turns model output into probabilities. The Silero models already end with a softmax, so
+/// only rows that aren't distributions are normalized.
+pub fn probabilities(output: ArrayView2<f32>) -> Array2<f32> {
+    let mut probs = output.to_owned();
+    for row in probs.rows_mut() {
+        let distribution = row.iter().all(|p| *p >= 0.0) && (row.sum() - 1.0).abs() < 1e-3;
+        if !distribution {
+            softmax_row(row);
         }
-        Ok(s.replace('$', "").trim().to_string())
     }
+    probs
 }
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("unknown audio format of {0:?}")]
+    UnknownFormat(PathBuf),
+    #[error("unknown audio format")]
+    UndetectedFormat,
+    #[error("unsupported extension {0}")]
+    UnsupportedExtension(String),
+    #[error("unsupported sample format {0}")]
+    UnsupportedSampleFormat(String),
+    #[error("no supported audio stream found")]
+    NoAudioStream,
+    #[error("invalid audio: {0}")]
+    InvalidAudio(String),
+    #[error("invalid raw audio format {0:?}, expected encoding:sample_rate:channels")]
+    InvalidRawFormat(String),
+    #[error(transparent)]
+    Wav(#[from] hound::Error),
+    #[error("demuxing failed: {0}")]
+    Demux(#[from] av_format::error::Error),
+    #[error("decoding failed: {0}")]
+    Decode(#[from] av_codec::error::Error),
+    #[error("decoding flac failed: {0}")]
+    Flac(#[from] claxon::Error),
+    #[error("demuxing ogg failed: {0}")]
+    Ogg(#[from] ogg::OggReadError),
+    #[error("decoding failed: {0}")]
+    Symphonia(#[from] symphonia::core::errors::Error),
+    #[error("resampling failed: {0}")]
+    ResamplerConstruction(#[from] rubato::ResamplerConstructionError),
+    #[error("resampling failed: {0}")]
+    Resample(#[from] rubato::ResampleError),
+    #[error("invalid labels: {0}")]
+    InvalidLabels(String),
+    #[error("invalid language model: {0}")]
+    InvalidLanguageModel(String),
+    #[error("invalid model: {0}")]
+    InvalidModel(String),
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("model {name:?} not found in {dir:?}, available: {available:?}")]
+    ModelNotFound {
+        name: String,
+        dir: PathBuf,
+        available: Vec<String>,
+    },
+    #[error("no model manifest or English model found in {0:?}, set SILERO_MODEL_DIR")]
+    NoModelDir(Vec<PathBuf>),
+    #[error("sequence of length {length} exceeds max sequence length {max}")]
+    SequenceTooLong { length: usize, max: usize },
+    #[error("unexpected model output: {0}")]
+    UnexpectedOutput(String),
+    #[error("invalid input {0:?}")]
+    InvalidInput(PathBuf),
+    #[error("unsupported output format {0}")]
+    UnsupportedOutputFormat(String),
+    #[error(transparent)]
+    Ort(#[from] ort::Error),
+    #[error(transparent)]
+    Shape(#[from] ndarray::ShapeError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Returns the error if the condition doesn't hold.
+macro_rules! ensure {
+    ($cond:expr, $err:expr) => {
+        if !$cond {
+            return Err($err);
+        }
+    };
+}
+
+pub(crate) use ensure;
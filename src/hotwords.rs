@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hotword {
+    pub phrase: String,
+    pub boost: f32,
+}
+
+impl Hotword {
+    pub fn new(phrase: impl Into<String>, boost: f32) -> Self {
+        Self {
+            phrase: phrase.into(),
+            boost,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    children: HashMap<usize, usize>,
+    boost: f32,
+    terminal: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HotwordState {
+    node: usize,
+    partial: f32,
+    complete: bool,
+}
+
+pub struct HotwordTrie {
+    nodes: Vec<Node>,
+}
+
+impl HotwordTrie {
+    pub fn new(hotwords: impl IntoIterator<Item = (Vec<usize>, f32)>) -> Self {
+        let mut nodes = vec![Node::default()];
+        for (labels, boost) in hotwords {
+            let mut node = 0;
+            for label in labels {
+                node = match nodes[node].children.get(&label) {
+                    Some(child) => *child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(label, child);
+                        child
+                    }
+                };
+                nodes[node].boost = nodes[node].boost.max(boost);
+            }
+            nodes[node].terminal = true;
+        }
+        Self { nodes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    /// Advances the match by one label and returns the change in score. A phrase only counts
+    /// once it is followed by a space or the end of the stream, partial matches that fail to
+    /// complete get their accumulated boost refunded.
+    pub fn advance(
+        &self,
+        state: &mut HotwordState,
+        label: usize,
+        word_start: bool,
+        space: bool,
+    ) -> f32 {
+        if state.complete && space {
+            state.partial = 0.0;
+        }
+        let mut delta = 0.0;
+        let mut child = self.nodes[state.node].children.get(&label);
+        if child.is_none() && state.node != 0 {
+            delta -= state.partial;
+            *state = HotwordState::default();
+            if word_start {
+                child = self.nodes[0].children.get(&label);
+            }
+        } else if state.node == 0 && !word_start {
+            child = None;
+        }
+        let Some(child) = child.copied() else {
+            return delta;
+        };
+        let node = &self.nodes[child];
+        delta += node.boost;
+        state.partial += node.boost;
+        state.complete = node.terminal;
+        state.node = child;
+        delta
+    }
+
+    pub fn finish(&self, state: &HotwordState) -> f32 {
+        if state.complete {
+            0.0
+        } else {
+            -state.partial
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::decoder::softmax;
+use crate::error::ensure;
+use crate::{
+    write_transcripts, DetectedLanguage, OutputFormat, RawFormat, Silero, SubtitleOptions,
+    Transcript,
+};
+use crate::{Error, Result};
+use ndarray::{Array2, Ix2};
+use ort::Session;
+use std::path::{Path, PathBuf};
+
+/// Spoken language classifier taking 16 kHz audio as `input` and returning one logit per
+/// language as `output`.
+pub struct LanguageIdentifier {
+    session: Session,
+    languages: Vec<String>,
+    /// Seconds from the start of the audio that are classified.
+    pub duration: f32,
+}
+
+impl LanguageIdentifier {
+    pub const SAMPLE_RATE: usize = 16000;
+
+    /// `languages` is a JSON list of the language codes of the model's outputs.
+    pub fn new(model: &[u8], languages: &str) -> Result<Self> {
+        Ok(Self {
+            session: crate::create_session(model)?,
+            languages: serde_json::from_str(languages)?,
+            duration: 5.0,
+        })
+    }
+
+    pub fn from_path(model: &Path, languages: &Path) -> Result<Self> {
+        Self::new(&std::fs::read(model)?, &std::fs::read_to_string(languages)?)
+    }
+
+    /// Returns the languages by decreasing probability.
+    pub fn identify(&self, samples: &[f32]) -> Result<Vec<DetectedLanguage>> {
+        let length = (self.duration * Self::SAMPLE_RATE as f32) as usize;
+        let samples = &samples[..length.min(samples.len())];
+        ensure!(
+            !samples.is_empty(),
+            Error::InvalidAudio("no audio to identify the language of".into())
+        );
+        let input = Array2::from_shape_vec((1, samples.len()), samples.to_vec())?.into_dyn();
+        let outputs = self.session.run(ort::inputs!["input" => &input])?;
+        let output = outputs["output"].extract_tensor::<f32>()?;
+        let logits = output.view().into_dimensionality::<Ix2>()?;
+        ensure!(
+            logits.dim() == (1, self.languages.len()),
+            Error::UnexpectedOutput(format!(
+                "expected {} languages, got shape {:?}",
+                self.languages.len(),
+                logits.dim()
+            ))
+        );
+        let probs = softmax(logits);
+        let mut languages: Vec<_> = self
+            .languages
+            .iter()
+            .zip(probs.row(0))
+            .map(|(language, probability)| DetectedLanguage {
+                language: language.clone(),
+                probability: *probability,
+            })
+            .collect();
+        languages.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        Ok(languages)
+    }
+}
+
+/// Transcribes each input with the model of its most likely language among the loaded ones.
+pub struct LanguageRouter {
+    identifier: LanguageIdentifier,
+    models: Vec<Silero>,
+    raw_format: Option<RawFormat>,
+}
+
+impl LanguageRouter {
+    pub fn new(identifier: LanguageIdentifier, models: Vec<Silero>) -> Result<Self> {
+        ensure!(
+            !models.is_empty(),
+            Error::InvalidConfig("no models to route to".into())
+        );
+        ensure!(
+            models
+                .iter()
+                .all(|silero| silero.model().language.is_some()),
+            Error::InvalidConfig("every model needs a language".into())
+        );
+        Ok(Self {
+            identifier,
+            models,
+            raw_format: None,
+        })
+    }
+
+    /// Reads the inputs as headerless audio of the given format, for all models.
+    pub fn set_raw_format(&mut self, raw_format: Option<RawFormat>) {
+        self.raw_format = raw_format;
+        for silero in &mut self.models {
+            silero.set_raw_format(raw_format);
+        }
+    }
+
+    pub fn transcribe(&self, inputs: &[PathBuf]) -> Result<Vec<Transcript>> {
+        let mut routes = vec![vec![]; self.models.len()];
+        let mut detected = Vec::with_capacity(inputs.len());
+        let model_languages: Vec<_> = self
+            .models
+            .iter()
+            .map(|silero| silero.model().language.as_deref().unwrap_or_default())
+            .collect();
+        for (i, input) in inputs.iter().enumerate() {
+            let (_, samples) = crate::audio::read_audio_file(
+                input,
+                self.raw_format,
+                LanguageIdentifier::SAMPLE_RATE,
+            )?;
+            let languages = self.identifier.identify(&samples)?;
+            let (model, language) = route(&model_languages, &languages);
+            routes[model].push(i);
+            detected.push(language);
+        }
+        let mut transcripts = vec![Transcript::default(); inputs.len()];
+        for (silero, route) in self.models.iter().zip(routes) {
+            if route.is_empty() {
+                continue;
+            }
+            let paths: Vec<_> = route.iter().map(|i| inputs[*i].clone()).collect();
+            for (i, transcript) in route.into_iter().zip(silero.transcribe(&paths)?) {
+                transcripts[i] = transcript;
+            }
+        }
+        for (transcript, language) in transcripts.iter_mut().zip(detected) {
+            transcript.language = Some(language);
+        }
+        Ok(transcripts)
+    }
+
+    pub fn stt(
+        &self,
+        inputs: &[PathBuf],
+        output: &Path,
+        format: OutputFormat,
+        options: &SubtitleOptions,
+    ) -> Result<()> {
+        let transcripts = self.transcribe(inputs)?;
+        write_transcripts(inputs, &transcripts, output, format, options)
+    }
+}
+
+/// Picks the model of the most likely language among `models`, returning its index with the
+/// language it transcribes. Falls back to the first model, with a probability of zero, if the
+/// identifier knows none of their languages.
+pub(crate) fn route(models: &[&str], languages: &[DetectedLanguage]) -> (usize, DetectedLanguage) {
+    for detected in languages {
+        if let Some(i) = models.iter().position(|model| *model == detected.language) {
+            return (i, detected.clone());
+        }
+    }
+    log::warn!(
+        "no model for the identified languages, transcribing with {:?}",
+        models[0]
+    );
+    let language = DetectedLanguage {
+        language: models[0].to_string(),
+        probability: 0.0,
+    };
+    (0, language)
+}
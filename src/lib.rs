@@ -1,63 +1,157 @@
-use crate::decoder::Decoder;
-use anyhow::{Context, Result};
-use ndarray::Array;
-use ort::{Environment, ExecutionProvider, Session, SessionBuilder};
-use std::fs::OpenOptions;
-use std::io::{BufWriter, Write};
+use crate::decoder::{probabilities, Decoder};
+use crate::error::ensure;
+use ndarray::{s, Array, Array2, Ix3};
+use ort::Session;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 mod audio;
+mod builder;
 mod decoder;
+mod error;
+mod hotwords;
+mod langid;
+mod lm;
+mod models;
+mod output;
+mod streaming;
+mod transcript;
+mod vad;
 
+pub use crate::audio::{
+    read_audio_from_reader, read_raw_audio, AudioFormat, AudioInfo, RawEncoding, RawFormat,
+};
+pub use crate::builder::SileroBuilder;
+pub use crate::decoder::{BeamSearchOptions, Hypothesis};
+pub use crate::error::{Error, Result};
+pub use crate::hotwords::Hotword;
+pub use crate::langid::{LanguageIdentifier, LanguageRouter};
+pub use crate::lm::LanguageModel;
+pub use crate::models::{find_model_dir, ModelManifest, ModelRegistry, MODEL_DIR_ENV};
+pub use crate::output::{cues, to_srt, to_vtt, Cue, OutputFormat, SubtitleOptions};
+pub use crate::streaming::{StreamingRecognizer, StreamingResult};
+pub use crate::transcript::{
+    DetectedLanguage, ModelInfo, Segment, Source, Token, Transcript, Word,
+};
+pub use crate::vad::{Vad, VadOptions};
+
+#[cfg(feature = "embedded-model")]
 const MODEL: &[u8] = include_bytes!("../models/en/en_v5.onnx");
+#[cfg(feature = "embedded-model")]
 const LABELS: &str = include_str!("../models/en/en_v1_labels.json");
 
+fn create_session(model: &[u8]) -> Result<Session> {
+    SileroBuilder::default().session(model)
+}
+
+fn fingerprint(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+struct Chunk {
+    input: usize,
+    window: Range<usize>,
+    continued: bool,
+}
+
 pub struct Silero {
     session: Session,
     decoder: Decoder,
     batch_size: usize,
     max_sequence_length: usize,
+    chunk_overlap: usize,
     sample_rate: usize,
+    fixed_batch_size: Option<usize>,
+    fixed_sequence_length: Option<usize>,
+    vad: Option<Vad>,
+    raw_format: Option<RawFormat>,
+    model: ModelInfo,
 }
 
 impl Silero {
+    pub fn builder() -> SileroBuilder {
+        SileroBuilder::default()
+    }
+
     pub fn new(model: &[u8], labels: &str) -> Result<Self> {
-        let environment = Environment::builder()
-            .with_name("silero")
-            .with_execution_providers([ExecutionProvider::CPU(Default::default())])
-            .build()?
-            .into_arc();
-        let session = SessionBuilder::new(&environment)?.with_model_from_memory(model)?;
-        let decoder = Decoder::from_json(labels)?;
-        Ok(Self {
-            session,
-            decoder,
-            batch_size: 10,
-            sample_rate: 16000,
-            max_sequence_length: 172800, //12800,
-        })
+        SileroBuilder::default().build(model, labels)
     }
 
     pub fn from_path(model: &Path, labels: &Path) -> Result<Self> {
-        let model = std::fs::read(model)?;
-        let labels = std::fs::read_to_string(labels)?;
-        Self::new(&model, &labels)
+        SileroBuilder::default().build_from_path(model, labels)
+    }
+
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        SileroBuilder::default().build_from_dir(dir)
     }
 
+    #[cfg(feature = "embedded-model")]
     pub fn default() -> Result<Self> {
-        Self::new(MODEL, LABELS)
+        SileroBuilder::default().build_default()
+    }
+
+    pub fn model(&self) -> &ModelInfo {
+        &self.model
+    }
+
+    pub fn set_vad(&mut self, vad: Option<Vad>) {
+        self.vad = vad;
+    }
+
+    /// Reads the inputs as headerless audio of the given format instead of sniffing their
+    /// container.
+    pub fn set_raw_format(&mut self, raw_format: Option<RawFormat>) {
+        self.raw_format = raw_format;
     }
 
     pub fn read_audio(&self, path: &Path) -> Result<Vec<f32>> {
-        crate::audio::read_audio(path, self.sample_rate)
+        Ok(crate::audio::read_audio_file(path, self.raw_format, self.sample_rate)?.1)
+    }
+
+    /// Reads audio in the given container, or in the one detected from the bytes if `None`.
+    pub fn read_audio_bytes(&self, bytes: &[u8], format: Option<AudioFormat>) -> Result<Vec<f32>> {
+        let reader = std::io::Cursor::new(bytes);
+        Ok(read_audio_from_reader(reader, format, self.sample_rate)?.1)
     }
 
     pub fn transcode_audio(&self, input: &Path, output: &Path) -> Result<()> {
         crate::audio::transcode_audio(input, output, self.sample_rate)
     }
 
-    pub fn infer(&self, batch: &[Vec<f32>]) -> Result<Vec<String>> {
-        let mut input = Array::zeros((batch.len(), self.max_sequence_length)).into_dyn();
+    fn sequence_length(&self, batch: &[Vec<f32>]) -> Result<usize> {
+        let longest = batch.iter().map(Vec::len).max().unwrap_or_default();
+        ensure!(
+            longest <= self.max_sequence_length,
+            Error::SequenceTooLong {
+                length: longest,
+                max: self.max_sequence_length,
+            }
+        );
+        if let Some(sequence_length) = self.fixed_sequence_length {
+            return Ok(sequence_length);
+        }
+        let buckets = longest.div_ceil(self.sample_rate).max(1);
+        Ok((buckets * self.sample_rate).min(self.max_sequence_length))
+    }
+
+    fn emissions(&self, batch: &[Vec<f32>]) -> Result<(Vec<Array2<f32>>, f32)> {
+        if batch.is_empty() {
+            return Ok((vec![], 0.0));
+        }
+        let sequence_length = self.sequence_length(batch)?;
+        let rows = self.fixed_batch_size.unwrap_or(batch.len());
+        ensure!(
+            batch.len() <= rows,
+            Error::InvalidConfig(format!(
+                "batch of {} inputs exceeds the model's batch size {}",
+                batch.len(),
+                rows
+            ))
+        );
+        let mut input = Array::zeros((rows, sequence_length)).into_dyn();
         for (i, samples) in batch.iter().enumerate() {
             for (j, sample) in samples.iter().enumerate() {
                 input[[i, j]] = *sample;
@@ -65,86 +159,208 @@ impl Silero {
         }
         let input_values = &input.as_standard_layout();
         let outputs = self.session.run(ort::inputs!["input" => input_values])?;
-        let tensor = outputs["output"]
-            .extract_tensor::<f32>()?
-            .view()
-            .t()
-            .into_owned();
-        let num_labels = tensor.slice(ndarray::s![.., 0, 0]).len();
-        let num_tokens = tensor.slice(ndarray::s![0, .., 0]).len();
-        let num_batches = tensor.slice(ndarray::s![0, 0, ..]).len();
-        anyhow::ensure!(num_labels == self.decoder.labels().len());
-        anyhow::ensure!(num_batches == batch.len());
-        let mut batch = Vec::with_capacity(num_batches);
-        let mut tokens = Vec::with_capacity(num_tokens);
-        for i in 0..num_batches {
-            for j in 0..num_tokens {
-                let probs = tensor.slice(ndarray::s![.., j, i]);
-                let (token, _) = probs
-                    .iter()
-                    .enumerate()
-                    .reduce(|(ia, a), (ib, b)| if a >= b { (ia, a) } else { (ib, b) })
-                    .unwrap();
-                tokens.push(token);
+        let output = outputs["output"].extract_tensor::<f32>()?;
+        let tensor = output.view().into_dimensionality::<Ix3>()?;
+        let (num_batches, num_tokens, num_labels) = tensor.dim();
+        ensure!(
+            num_labels == self.decoder.labels().len() && num_batches == rows,
+            Error::UnexpectedOutput(format!(
+                "expected {} labels for {} rows, got shape {:?}",
+                self.decoder.labels().len(),
+                rows,
+                tensor.dim()
+            ))
+        );
+        let samples_per_frame = sequence_length as f32 / num_tokens as f32;
+        let frame_stride = samples_per_frame / self.sample_rate as f32;
+        let probs = batch
+            .iter()
+            .enumerate()
+            .map(|(i, samples)| {
+                let frames = (samples.len() as f32 / samples_per_frame).ceil() as usize;
+                probabilities(tensor.slice(s![i, ..frames.min(num_tokens), ..]))
+            })
+            .collect();
+        Ok((probs, frame_stride))
+    }
+
+    pub fn infer(&self, batch: &[Vec<f32>]) -> Result<Vec<Transcript>> {
+        let (probs, frame_stride) = self.emissions(batch)?;
+        Ok(probs
+            .iter()
+            .map(|probs| self.decoder.greedy(probs.view(), frame_stride))
+            .collect())
+    }
+
+    pub fn infer_beam(
+        &self,
+        batch: &[Vec<f32>],
+        options: &BeamSearchOptions,
+    ) -> Result<Vec<Vec<Hypothesis>>> {
+        let (probs, frame_stride) = self.emissions(batch)?;
+        Ok(probs
+            .iter()
+            .map(|probs| {
+                self.decoder
+                    .beam_search(probs.view(), frame_stride, options)
+            })
+            .collect())
+    }
+
+    fn windows(&self, len: usize) -> Vec<Range<usize>> {
+        let step = self.max_sequence_length - self.chunk_overlap;
+        let mut windows = vec![];
+        let mut start = 0;
+        loop {
+            let end = (start + self.max_sequence_length).min(len);
+            windows.push(start..end);
+            if end == len {
+                return windows;
             }
-            let decoded = self.decoder.decode(&tokens)?;
-            batch.push(decoded);
-            tokens.clear();
+            start += step;
         }
-        Ok(batch)
     }
 
-    fn process_batch(&self, batch: &[Vec<f32>], outputs: &[PathBuf]) -> Result<()> {
-        let results = self.infer(batch)?;
-        for (result, output) in results.iter().zip(outputs) {
-            let mut w = BufWriter::new(OpenOptions::new().append(true).open(output)?);
-            w.write_all(result.as_bytes())?;
+    fn process_batch(
+        &self,
+        batch: &[Vec<f32>],
+        chunks: &[Chunk],
+        transcripts: &mut [Transcript],
+        options: Option<&BeamSearchOptions>,
+    ) -> Result<()> {
+        let results = match options {
+            Some(options) => self
+                .infer_beam(batch, options)?
+                .into_iter()
+                .map(|hypotheses| {
+                    hypotheses
+                        .into_iter()
+                        .next()
+                        .map(|hypothesis| hypothesis.transcript)
+                        .unwrap_or_default()
+                })
+                .collect(),
+            None => self.infer(batch)?,
+        };
+        let sample_rate = self.sample_rate as f32;
+        for (mut result, chunk) in results.into_iter().zip(chunks) {
+            let start = chunk.window.start as f32 / sample_rate;
+            result.shift(start);
+            if chunk.continued {
+                let overlap = start..start + self.chunk_overlap as f32 / sample_rate;
+                transcripts[chunk.input].stitch(result, overlap);
+            } else {
+                transcripts[chunk.input].append(result);
+            }
         }
         Ok(())
     }
 
-    pub fn stt(&self, inputs: &[PathBuf], output: &Path) -> Result<()> {
+    pub fn transcribe(&self, inputs: &[PathBuf]) -> Result<Vec<Transcript>> {
+        self.transcribe_with(inputs, None)
+    }
+
+    /// Transcribes the inputs like [`transcribe`](Self::transcribe) but decodes them with beam
+    /// search, e.g. to fuse a language model or boost hotwords.
+    pub fn transcribe_beam(
+        &self,
+        inputs: &[PathBuf],
+        options: &BeamSearchOptions,
+    ) -> Result<Vec<Transcript>> {
+        self.transcribe_with(inputs, Some(options))
+    }
+
+    fn transcribe_with(
+        &self,
+        inputs: &[PathBuf],
+        options: Option<&BeamSearchOptions>,
+    ) -> Result<Vec<Transcript>> {
+        let mut transcripts = vec![Transcript::default(); inputs.len()];
         let mut batch = Vec::with_capacity(self.batch_size);
-        let mut outputs = Vec::with_capacity(self.batch_size);
-        for input in inputs {
-            let samples = self.read_audio(input)?;
-            let basename = input
-                .file_stem()
-                .context("invalid input")?
-                .to_str()
-                .context("invalid input")?;
-            let output = output.join(format!("{basename}.txt"));
-            OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&output)?;
-            for chunk in samples.chunks(self.max_sequence_length) {
-                batch.push(chunk.to_vec());
-                outputs.push(output.clone());
-                if batch.len() == self.batch_size {
-                    self.process_batch(&batch, &outputs)?;
-                    batch.clear();
-                    outputs.clear();
+        let mut chunks = Vec::with_capacity(self.batch_size);
+        for (i, input) in inputs.iter().enumerate() {
+            let (info, samples) =
+                crate::audio::read_audio_file(input, self.raw_format, self.sample_rate)?;
+            transcripts[i].source = Some(Source {
+                path: Some(input.clone()),
+                info,
+            });
+            transcripts[i].model = Some(self.model.clone());
+            let segments = match self.vad.as_ref() {
+                Some(vad) => vad.segments(&samples, self.sample_rate)?,
+                None => vec![0..samples.len()],
+            };
+            for segment in segments {
+                for (j, window) in self.windows(segment.len()).into_iter().enumerate() {
+                    let window = segment.start + window.start..segment.start + window.end;
+                    batch.push(samples[window.clone()].to_vec());
+                    chunks.push(Chunk {
+                        input: i,
+                        window,
+                        continued: j > 0,
+                    });
+                    if batch.len() == self.batch_size {
+                        self.process_batch(&batch, &chunks, &mut transcripts, options)?;
+                        batch.clear();
+                        chunks.clear();
+                    }
                 }
             }
         }
-        if !inputs.is_empty() {
-            self.process_batch(&batch, &outputs)?;
-        }
-        Ok(())
+        self.process_batch(&batch, &chunks, &mut transcripts, options)?;
+        Ok(transcripts)
+    }
+
+    pub fn stt(
+        &self,
+        inputs: &[PathBuf],
+        output: &Path,
+        format: OutputFormat,
+        options: &SubtitleOptions,
+    ) -> Result<()> {
+        let transcripts = self.transcribe(inputs)?;
+        write_transcripts(inputs, &transcripts, output, format, options)
     }
 }
 
+fn write_transcripts(
+    inputs: &[PathBuf],
+    transcripts: &[Transcript],
+    output: &Path,
+    format: OutputFormat,
+    options: &SubtitleOptions,
+) -> Result<()> {
+    for (input, transcript) in inputs.iter().zip(transcripts) {
+        let basename = input
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| Error::InvalidInput(input.clone()))?;
+        let output = output.join(format!("{basename}.{}", format.extension()));
+        std::fs::write(output, format.render(transcript, options)?)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anyhow::{Context, Result};
+    use std::sync::Arc;
 
     const INPUT_WEBM: &str = "example/speech_orig_opus.webm";
     const INPUT_WEBA: &str = "example/speech_orig_vorbis.weba";
     const INPUT_WAV: &str = "example/speech_orig_pcm.wav";
+    const INPUT_FLAC: &str = "example/speech_orig_24bit.flac";
+    const INPUT_OPUS: &str = "example/speech_orig_opus.opus";
+    const INPUT_OGG: &str = "example/speech_orig_vorbis.ogg";
+    const INPUT_MP3_STEREO: &str = "example/tone_vbr_stereo.mp3";
+    const INPUT_MP3_MONO: &str = "example/tone_mono.mp3";
+    const INPUT_M4A: &str = "example/tone_aac.m4a";
+    const INPUT_OGG_STEREO: &str = "example/tone_vorbis_stereo.ogg";
+    const INPUT_OGG_MONO: &str = "example/tone_vorbis_mono.ogg";
     const OUTPUT_WAV: &str = "example/speech_orig_transcoded.wav";
     const INPUT_TENSOR: &str = "example/input.json";
+    const MODEL_DIR: &str = "models/en";
     const TOKENS: &[usize] = &[
         0, 2, 998, 157, 0, 38, 0, 998, 135, 972, 972, 969, 969, 975, 978, 978, 44, 998, 7, 998, 2,
         998, 975, 71, 972, 1, 998, 748, 0, 616, 616, 0, 0, 998, 0, 250, 983, 983, 998, 2, 998, 998,
@@ -154,60 +370,758 @@ mod tests {
         998, 0, 515, 0, 998, 998, 998, 906, 0, 975, 975, 998, 998, 998, 20, 998, 998, 473, 473, 55,
         986, 986, 998, 301, 0, 0, 998, 848, 14, 14, 998, 998, 80, 0, 0, 0, 0,
     ];
+    const ARPA: &str = r"
+\data\
+ngram 1=6
+ngram 2=2
+
+\1-grams:
+-1.0 <s> -0.1
+-1.0 </s>
+-2.0 <unk>
+-1.0 the -0.5
+-1.0 depth
+-1.5 well
+
+\2-grams:
+-0.2 <s> the
+-0.3 the well
+
+\end\
+";
     const TEXT: &str = "the boch canoeslid on the smooth planks blew the sheet to the dark blue background it's easy to tell a deps of a well four hours of steady work faced us";
     const TEXT2: &str = "the boch canoe slid on the smooth planks blew the sheet to the dark blue background it's easy to tell a depth of a well four hours of steady work faced us";
     const TEXT3: &str = "the boch canoeslid on the smooth planks blew the sheet to the dark blue background it's easy to tell a debts of a well four hours of steady work faced us";
     const TEXT4: &str = "the boch canoeslit on the smooth planks blew the sheet to the dark blue background it's easy to tell aaddepth a well four hours of steady work faced us";
 
+    fn decoder() -> Result<Decoder> {
+        Ok(Decoder::from_path(
+            &Path::new(MODEL_DIR).join(ModelManifest::default().labels),
+        )?)
+    }
+
+    fn silero() -> Result<Silero> {
+        Ok(Silero::from_dir(MODEL_DIR.as_ref())?)
+    }
+
     #[test]
     fn test_decoder() -> Result<()> {
-        let decoder = Decoder::from_json(LABELS.as_ref())?;
+        let decoder = decoder()?;
         assert_eq!(decoder.decode(&TOKENS)?, TEXT);
         Ok(())
     }
 
+    #[test]
+    fn test_decoder_align() -> Result<()> {
+        let decoder = decoder()?;
+        let transcript = decoder.align(&TOKENS, &[1.0; TOKENS.len()], 0.08);
+        assert_eq!(transcript.text, TEXT);
+        assert_eq!(transcript.confidence, 1.0);
+        let words: Vec<_> = transcript.words().collect();
+        let text: Vec<_> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(text, TEXT.split(' ').collect::<Vec<_>>());
+        for (prev, next) in words.iter().zip(&words[1..]) {
+            assert!(prev.start < prev.end);
+            assert!(prev.end <= next.start);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let output = ndarray::array![[0.7, 0.2, 0.1], [2.0, 1.0, 0.0]];
+        let probs = probabilities(output.view());
+        assert_eq!(probs.row(0), output.row(0));
+        assert!((probs.row(1).sum() - 1.0).abs() < 1e-6);
+        assert!(probs[[1, 0]] > probs[[1, 1]] && probs[[1, 1]] > probs[[1, 2]]);
+    }
+
+    #[test]
+    fn test_beam_search() -> Result<()> {
+        let decoder = decoder()?;
+        let num_labels = decoder.labels().len();
+        let mut probs = Array2::from_elem((TOKENS.len(), num_labels), 0.1 / num_labels as f32);
+        for (i, token) in TOKENS.iter().enumerate() {
+            probs[[i, *token]] += 0.9;
+        }
+        let options = BeamSearchOptions {
+            beam_width: 8,
+            n_best: 3,
+            ..Default::default()
+        };
+        let hypotheses = decoder.beam_search(probs.view(), 0.08, &options);
+        assert_eq!(hypotheses.len(), 3);
+        assert_eq!(hypotheses[0].transcript.text, TEXT);
+        assert!(hypotheses[0].score >= hypotheses[1].score);
+        assert!(hypotheses[1].score >= hypotheses[2].score);
+        Ok(())
+    }
+
+    #[test]
+    fn test_language_model() -> Result<()> {
+        let lm = LanguageModel::from_arpa(ARPA)?;
+        assert_eq!(lm.order(), 2);
+        let ln = |log10: f32| log10 * std::f32::consts::LN_10;
+        assert_eq!(lm.score(&["<s>"], "the"), ln(-0.2));
+        assert_eq!(lm.score(&["<s>", "the"], "depth"), ln(-0.5 - 1.0));
+        assert_eq!(lm.score(&["the"], "well"), ln(-0.3));
+        assert_eq!(lm.score(&["the"], "canoeslid"), ln(-0.5 - 2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hotwords() -> Result<()> {
+        let decoder = decoder()?;
+        let labels = decoder.tokenize("Canoe slid").context("tokenize")?;
+        assert_eq!(decoder.decode(&labels)?, "canoe slid");
+        Ok(())
+    }
+
+    /// A decoder with single letter labels, which doesn't need the model.
+    fn letters() -> Result<Decoder> {
+        let labels = ["_", "2", " "]
+            .into_iter()
+            .map(String::from)
+            .chain(('a'..='z').map(String::from))
+            .collect();
+        Ok(Decoder::new(labels)?)
+    }
+
+    /// Builds one row of probabilities per frame from the given labels, the rest goes to blank.
+    fn frames(decoder: &Decoder, frames: &[&[(&str, f32)]]) -> Array2<f32> {
+        let labels = decoder.labels();
+        let mut probs = Array2::from_elem((frames.len(), labels.len()), 1e-3);
+        for (i, frame) in frames.iter().enumerate() {
+            probs[[i, 0]] = 1.0 - frame.iter().map(|(_, p)| p).sum::<f32>();
+            for (label, p) in frame.iter() {
+                let label = labels.iter().position(|l| l == label).unwrap();
+                probs[[i, label]] = *p;
+            }
+        }
+        probs
+    }
+
+    #[test]
+    fn test_beam_search_hotwords() -> Result<()> {
+        let decoder = letters()?;
+        let search = |frames: &[&[(&str, f32)]], hotwords: &[&str]| {
+            let options = BeamSearchOptions {
+                hotwords: hotwords.iter().map(|p| Hotword::new(*p, 1.0)).collect(),
+                ..Default::default()
+            };
+            let probs = self::frames(&decoder, frames);
+            let hypotheses = decoder.beam_search(probs.view(), 0.08, &options);
+            hypotheses[0].transcript.text.clone()
+        };
+        let ban: &[&[(&str, f32)]] = &[&[("b", 0.5), ("c", 0.4)], &[("a", 0.9)], &[("n", 0.9)]];
+        assert_eq!(search(ban, &[]), "ban");
+        assert_eq!(search(ban, &["can"]), "can");
+        let ban_is = [ban, &[&[(" ", 0.9)], &[("i", 0.9)], &[("s", 0.9)]]].concat();
+        assert_eq!(search(&ban_is, &["can"]), "can is");
+        // "can" doesn't boost words it's a prefix of
+        let banoe = [ban, &[&[("o", 0.9)], &[("e", 0.9)]]].concat();
+        assert_eq!(search(&banoe, &["can"]), "banoe");
+        assert_eq!(search(&banoe, &["canoe"]), "canoe");
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_search_lm() -> Result<()> {
+        let decoder = letters()?;
+        let lm = Arc::new(LanguageModel::from_arpa(ARPA)?);
+        let search = |lm_weight: f32| {
+            let options = BeamSearchOptions {
+                lm: Some(lm.clone()),
+                lm_weight,
+                ..Default::default()
+            };
+            let probs = frames(
+                &decoder,
+                &[
+                    &[("t", 0.9)],
+                    &[("h", 0.9)],
+                    &[("e", 0.9)],
+                    &[(" ", 0.9)],
+                    &[("w", 0.9)],
+                    &[("a", 0.5), ("e", 0.4)],
+                    &[("l", 0.9)],
+                    &[("2", 0.9)],
+                ],
+            );
+            let hypotheses = decoder.beam_search(probs.view(), 0.08, &options);
+            hypotheses[0].transcript.text.clone()
+        };
+        assert_eq!(search(0.0), "the wall");
+        assert_eq!(search(0.5), "the well");
+        Ok(())
+    }
+
+    fn transcript(words: &[(&str, f32, f32)]) -> Transcript {
+        let words = words
+            .iter()
+            .map(|(text, start, end)| {
+                Word::new(vec![Token {
+                    text: text.to_string(),
+                    start: *start,
+                    end: *end,
+                    confidence: 1.0,
+                }])
+            })
+            .collect();
+        Transcript::from_segments(vec![Segment::new(0.0, 10.0, words)])
+    }
+
+    #[test]
+    fn test_stitch() {
+        let mut first = transcript(&[("four", 8.0, 8.3), ("hours", 8.4, 8.8), ("of", 9.5, 9.6)]);
+        let second = transcript(&[("ours", 8.0, 8.3), ("hours", 8.45, 8.8), ("of", 9.5, 9.6)]);
+        first.stitch(second, 8.0..10.0);
+        assert_eq!(first.text, "four hours of");
+
+        let mut first = transcript(&[("a", 1.0, 1.2), ("dep", 8.9, 9.2)]);
+        let second = transcript(&[("ths", 8.7, 9.1), ("of", 9.5, 9.6)]);
+        first.stitch(second, 8.0..10.0);
+        assert_eq!(first.text, "a dep of");
+    }
+
+    #[test]
+    fn test_subtitles() {
+        let transcript = transcript(&[
+            ("it's", 0.5, 0.7),
+            ("easy", 0.8, 1.1),
+            ("to", 1.2, 1.3),
+            ("tell", 1.4, 1.7),
+            ("a", 1.8, 1.9),
+            ("depth", 2.0, 2.4),
+            ("of", 2.5, 2.6),
+            ("a", 2.7, 2.8),
+            ("well", 2.9, 3.3),
+        ]);
+        let options = SubtitleOptions {
+            max_line_length: 12,
+            max_lines: 2,
+            max_cue_duration: 1.5,
+        };
+        let srt = to_srt(&transcript, &options);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,500 --> 00:00:01,900\nit's easy to\ntell a\n\n\
+             2\n00:00:02,000 --> 00:00:03,300\ndepth of a\nwell\n\n"
+        );
+        let vtt = to_vtt(&transcript, &options);
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.500 --> 00:00:01.900\n"));
+    }
+
+    #[test]
+    fn test_json() -> Result<()> {
+        let mut transcript = transcript(&[("a", 1.0, 1.2), ("well", 1.3, 1.6)]);
+        transcript.source = Some(Source {
+            path: Some(INPUT_WAV.into()),
+            info: AudioInfo {
+                format: Some(AudioFormat::Wav),
+                sample_rate: 16000,
+                channels: 1,
+                duration: 2.0,
+            },
+        });
+        let json = OutputFormat::Json.render(&transcript, &Default::default())?;
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(value["text"], "a well");
+        assert_eq!(value["source"]["sample_rate"], 16000);
+        assert_eq!(value["source"]["format"], "wav");
+        assert_eq!(value["segments"][0]["words"][1]["text"], "well");
+        assert!(value.get("model").is_none());
+        assert_eq!(serde_json::from_str::<Transcript>(&json)?, transcript);
+        Ok(())
+    }
+
+    #[test]
+    fn test_energy_vad() -> Result<()> {
+        let sample_rate = 16000;
+        let mut samples = vec![0.0; sample_rate * 3];
+        for (i, sample) in samples[16384..32768].iter_mut().enumerate() {
+            *sample = (i as f32 * 0.05).sin() * 0.5;
+        }
+        let vad = Vad::energy(-40.0);
+        let segments = vad.segments(&samples, sample_rate)?;
+        let pad = (vad.options.speech_pad * sample_rate as f32) as usize;
+        assert_eq!(segments, vec![16384 - pad..32768 + pad]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_dir() -> Result<()> {
+        std::env::set_var(MODEL_DIR_ENV, MODEL_DIR);
+        assert_eq!(find_model_dir()?, Path::new(MODEL_DIR));
+        Ok(())
+    }
+
+    #[test]
+    fn test_registry() -> Result<()> {
+        let registry = ModelRegistry::open("models".as_ref())?;
+        let manifest = ModelManifest {
+            model: "en/en_v5.onnx".into(),
+            labels: "en/en_v1_labels.json".into(),
+            ..Default::default()
+        };
+        assert_eq!(registry.find("en"), Some(&manifest));
+        assert_eq!(registry.find("en_v5"), Some(&manifest));
+        assert_eq!(registry.find("de"), None);
+        assert!(registry.load("de", Silero::builder()).is_err());
+        assert_eq!(
+            ModelRegistry::open(MODEL_DIR.as_ref())?.models(),
+            &[ModelManifest::default()]
+        );
+        Ok(())
+    }
+
+    fn detected(languages: &[(&str, f32)]) -> Vec<DetectedLanguage> {
+        languages
+            .iter()
+            .map(|(language, probability)| DetectedLanguage {
+                language: language.to_string(),
+                probability: *probability,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_route() {
+        let languages = detected(&[("de", 0.6), ("en", 0.3), ("es", 0.1)]);
+        assert_eq!(
+            langid::route(&["en", "de"], &languages),
+            (1, languages[0].clone())
+        );
+        assert_eq!(
+            langid::route(&["es", "en"], &languages),
+            (1, languages[1].clone())
+        );
+        // without a model for any identified language, the one used is reported
+        let (model, language) = langid::route(&["uk"], &languages);
+        assert_eq!((model, language.language.as_str()), (0, "uk"));
+        assert_eq!(language.probability, 0.0);
+    }
+
+    #[test]
+    fn test_language_router() -> Result<()> {
+        let manifest = ModelManifest::default();
+        let model = Path::new(MODEL_DIR).join(&manifest.model);
+        // any model loads as an identifier, the router only checks its models here
+        let identifier = || LanguageIdentifier::new(&std::fs::read(&model)?, r#"["en", "de"]"#);
+        assert!(LanguageRouter::new(identifier()?, vec![]).is_err());
+        let unnamed = Silero::from_path(&model, &Path::new(MODEL_DIR).join(&manifest.labels))?;
+        assert!(LanguageRouter::new(identifier()?, vec![unnamed]).is_err());
+        assert!(LanguageRouter::new(identifier()?, vec![silero()?]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder() -> Result<()> {
+        assert!(Silero::builder()
+            .batch_size(0)
+            .build_from_dir(MODEL_DIR.as_ref())
+            .is_err());
+        assert!(Silero::builder()
+            .window_overlap(12.0)
+            .build_from_dir(MODEL_DIR.as_ref())
+            .is_err());
+        assert!(Silero::builder()
+            .intra_threads(1 << 16)
+            .build_from_dir(MODEL_DIR.as_ref())
+            .is_err());
+        let silero = Silero::builder()
+            .batch_size(2)
+            .window_length(4.0)
+            .sample_rate(8000)
+            .intra_threads(1)
+            .build_from_dir(MODEL_DIR.as_ref())?;
+        assert_eq!(silero.batch_size, 2);
+        assert_eq!(silero.max_sequence_length, 32000);
+        assert_eq!(silero.chunk_overlap, 16000);
+        let silero = silero()?;
+        assert_eq!(silero.max_sequence_length, 172800);
+        assert_eq!(silero.chunk_overlap, 32000);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-model")]
+    fn test_embedded_model() -> Result<()> {
+        let embedded = Silero::default()?;
+        assert_eq!(embedded.model().name, "en_v5");
+        assert_eq!(embedded.model().fingerprint, silero()?.model().fingerprint);
+        Ok(())
+    }
+
     #[test]
     fn test_inference() -> Result<()> {
         let bytes = std::fs::read(INPUT_TENSOR)?;
         let tensor: Vec<Vec<f32>> = serde_json::from_slice(&bytes)?;
-        let silero = Silero::default()?;
+        let silero = silero()?;
         let result = silero.infer(&tensor)?;
-        assert_eq!(result[0], TEXT);
+        assert_eq!(result[0].text, TEXT);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_trimming() -> Result<()> {
+        let silero = silero()?;
+        let long = silero.read_audio(INPUT_WAV.as_ref())?;
+        let short = long[..silero.sample_rate * 3 / 4].to_vec();
+        assert_eq!(
+            silero.sequence_length(&[short.clone()])?,
+            silero.sample_rate
+        );
+        assert!(silero.sequence_length(&[long.clone(), short.clone()])? >= long.len());
+        let (probs, frame_stride) = silero.emissions(&[long.clone(), short.clone()])?;
+        let samples_per_frame = frame_stride * silero.sample_rate as f32;
+        assert!((probs[1].nrows() as f32 - short.len() as f32 / samples_per_frame).abs() < 1.0);
+        assert!(probs[1].nrows() < probs[0].nrows());
+        // no words are decoded from the padding after the short clip
+        let duration = short.len() as f32 / silero.sample_rate as f32;
+        let transcripts = silero.infer(&[long, short])?;
+        assert!(transcripts[1]
+            .words()
+            .all(|word| word.end <= duration + frame_stride));
         Ok(())
     }
 
     #[test]
     fn test_pipeline_wav() -> Result<()> {
-        let silero = Silero::default()?;
+        let silero = silero()?;
         let samples = silero.read_audio(INPUT_WAV.as_ref())?;
         let result = silero.infer(&[samples])?;
-        assert_eq!(result[0], TEXT2);
+        assert_eq!(result[0].text, TEXT2);
+        assert!(result[0].confidence > 0.5 && result[0].confidence <= 1.0);
         Ok(())
     }
 
     #[test]
     fn test_pipeline_webm() -> Result<()> {
-        let silero = Silero::default()?;
+        let silero = silero()?;
         let samples = silero.read_audio(INPUT_WEBM.as_ref())?;
         let result = silero.infer(&[samples])?;
-        assert_eq!(result[0], TEXT3);
+        assert_eq!(result[0].text, TEXT3);
         Ok(())
     }
 
     #[test]
     fn test_pipeline_weba() -> Result<()> {
-        let silero = Silero::default()?;
+        let silero = silero()?;
         let mut samples = silero.read_audio(INPUT_WEBA.as_ref())?;
         samples.truncate(silero.max_sequence_length);
         let result = silero.infer(&[samples])?;
-        assert_eq!(result[0], TEXT4);
+        assert_eq!(result[0].text, TEXT4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming() -> Result<()> {
+        let silero = silero()?;
+        let samples = silero.read_audio(INPUT_WAV.as_ref())?;
+        let mut recognizer = StreamingRecognizer::new(&silero);
+        let mut partials = 0;
+        let mut finals = vec![];
+        for chunk in samples.chunks(4000) {
+            recognizer.push_samples(chunk)?;
+            for result in recognizer.results() {
+                match result {
+                    StreamingResult::Partial(_) => partials += 1,
+                    StreamingResult::Final(transcript) => finals.push(transcript.text),
+                }
+            }
+        }
+        recognizer.finish()?;
+        for result in recognizer.results() {
+            if let StreamingResult::Final(transcript) = result {
+                finals.push(transcript.text);
+            }
+        }
+        assert!(partials > 0);
+        assert!(finals.join(" ").contains("four hours of steady work"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_horizon() {
+        let transcript = transcript(&[("four", 0.5, 0.9), ("hours", 1.8, 2.3), ("of", 2.5, 2.6)]);
+        let (words, end) = streaming::final_words(&transcript, 2.0, 1.0);
+        assert_eq!(words.len(), 1);
+        assert_eq!(end, 1.0);
+        // "hours" straddles the horizon but starts before the cut the next window needs
+        let (words, end) = streaming::final_words(&transcript, 2.0, 1.9);
+        let text: Vec<_> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(text, ["four", "hours"]);
+        assert_eq!(end, 2.3);
+        let (words, end) = streaming::final_words(&transcript, 0.2, 0.4);
+        assert!(words.is_empty());
+        assert_eq!(end, 0.4);
+        // a single word straddling the horizon is left whole for the next window
+        let transcript = self::transcript(&[("hours", 1.8, 2.3)]);
+        let (words, end) = streaming::final_words(&transcript, 2.0, 1.0);
+        assert!(words.is_empty());
+        assert_eq!(end, 1.8);
+    }
+
+    #[test]
+    fn test_transcribe_beam() -> Result<()> {
+        // short windows, so the input is split and stitched
+        let silero = Silero::builder()
+            .window_length(4.0)
+            .build_from_dir(MODEL_DIR.as_ref())?;
+        let options = BeamSearchOptions {
+            hotwords: vec![Hotword::new("steady", 2.0)],
+            ..Default::default()
+        };
+        let transcripts = silero.transcribe_beam(&[INPUT_WAV.into()], &options)?;
+        assert!(transcripts[0].text.contains("four hours of steady work"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_audio_bytes() -> Result<()> {
+        for (path, format) in [
+            (INPUT_WAV, AudioFormat::Wav),
+            (INPUT_WEBM, AudioFormat::Matroska),
+        ] {
+            let bytes = std::fs::read(path)?;
+            let expected = audio::read_audio(path.as_ref(), 16000)?;
+            for format in [Some(format), None] {
+                let reader = std::io::Cursor::new(&bytes);
+                let (info, samples) = read_audio_from_reader(reader, format, 16000)?;
+                assert_eq!(&(info, samples), &expected);
+            }
+        }
+        let reader = std::io::Cursor::new(b"not audio");
+        assert!(matches!(
+            read_audio_from_reader(reader, None, 16000),
+            Err(Error::UndetectedFormat)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_flac() -> Result<()> {
+        let (info, samples) = audio::read_audio(INPUT_FLAC.as_ref(), 48000)?;
+        assert_eq!(info.format, Some(AudioFormat::Flac));
+        assert_eq!((info.sample_rate, info.channels), (48000, 2));
+        assert_eq!(info.duration, 0.5);
+        let (_, expected) = audio::read_audio(INPUT_WAV.as_ref(), 48000)?;
+        assert_eq!(samples.len(), 24000);
+        for (sample, expected) in samples.iter().zip(&expected) {
+            assert!((sample - expected).abs() < 1e-4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ogg() -> Result<()> {
+        for (ogg, webm) in [(INPUT_OPUS, INPUT_WEBM), (INPUT_OGG, INPUT_WEBA)] {
+            let (info, samples) = audio::read_audio(ogg.as_ref(), 16000)?;
+            assert_eq!(info.format, Some(AudioFormat::Ogg));
+            assert_eq!((info.sample_rate, info.channels), (48000, 1));
+            let (_, expected) = audio::read_audio(webm.as_ref(), 16000)?;
+            assert_eq!(samples, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ogg_stereo() -> Result<()> {
+        // a tone in the left channel and silence in the right one, next to the left channel alone
+        let (info, samples) = audio::read_audio(INPUT_OGG_STEREO.as_ref(), 16000)?;
+        assert_eq!((info.sample_rate, info.channels), (16000, 2));
+        let (_, left) = audio::read_audio(INPUT_OGG_MONO.as_ref(), 16000)?;
+        assert!(left.iter().any(|sample| sample.abs() > 0.1));
+        let expected: Vec<f32> = left.iter().map(|sample| sample / 2.0).collect();
+        assert_eq!(samples, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ogg_without_channels() -> Result<()> {
+        let opus = [b"OpusHead\x01\x00".as_slice(), &[0; 9]].concat();
+        let vorbis = [
+            b"\x01vorbis".as_slice(),
+            &[0; 5],
+            &16000u32.to_le_bytes(),
+            &[0; 14],
+        ]
+        .concat();
+        for head in [opus, vorbis] {
+            let mut bytes = vec![];
+            let mut writer = ogg::writing::PacketWriter::new(&mut bytes);
+            writer.write_packet(
+                head.into_boxed_slice(),
+                1,
+                ogg::writing::PacketWriteEndInfo::EndStream,
+                0,
+            )?;
+            drop(writer);
+            let reader = std::io::Cursor::new(bytes);
+            assert!(matches!(
+                read_audio_from_reader(reader, Some(AudioFormat::Ogg), 16000),
+                Err(Error::InvalidAudio(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_mp3() -> Result<()> {
+        for (path, sample_rate, channels) in
+            [(INPUT_MP3_STEREO, 44100, 2), (INPUT_MP3_MONO, 32000, 1)]
+        {
+            let (info, samples) = audio::read_audio(path.as_ref(), 16000)?;
+            assert_eq!(info.format, Some(AudioFormat::Mp3));
+            assert_eq!((info.sample_rate, info.channels), (sample_rate, channels));
+            assert!(info.duration > 1.0);
+            assert!(samples.iter().any(|sample| sample.abs() > 0.1));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_m4a() -> Result<()> {
+        // the first track of the file is a timed text track
+        let (info, samples) = audio::read_audio(INPUT_M4A.as_ref(), 16000)?;
+        assert_eq!(info.format, Some(AudioFormat::Mp4));
+        assert_eq!((info.sample_rate, info.channels), (44100, 1));
+        assert!(info.duration > 1.0);
+        assert!(samples.iter().any(|sample| sample.abs() > 0.1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw() -> Result<()> {
+        // the wav input is 48 kHz mono s16le after its 44 byte header
+        let bytes = std::fs::read(INPUT_WAV)?;
+        let format: RawFormat = "s16le:48000:1".parse()?;
+        let (info, samples) = read_raw_audio(&bytes[44..], format, 16000)?;
+        assert_eq!(info.format, None);
+        assert_eq!((info.sample_rate, info.channels), (48000, 1));
+        assert_eq!(samples, audio::read_audio(INPUT_WAV.as_ref(), 16000)?.1);
+
+        let format: RawFormat = "mulaw:8000:1".parse()?;
+        assert_eq!(format.to_string(), "mulaw:8000:1");
+        let (_, samples) = read_raw_audio(&[0xff, 0x00, 0x80][..], format, 8000)?;
+        assert_eq!(samples, [0.0, -32124.0 / 32767.0, 32124.0 / 32767.0]);
+        let format = RawFormat {
+            encoding: RawEncoding::ALaw,
+            ..format
+        };
+        let (_, samples) = read_raw_audio(&[0xd5, 0x55][..], format, 8000)?;
+        assert_eq!(samples, [8.0 / 32767.0, -8.0 / 32767.0]);
+        assert!("s16le:8000".parse::<RawFormat>().is_err());
+        assert!("s24le:8000:1".parse::<RawFormat>().is_err());
+        Ok(())
+    }
+
+    /// Builds a mono 8 kHz wav file with the given format tag.
+    fn wav(format_tag: u16, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut wav = b"RIFF".to_vec();
+        wav.extend((36 + data.len() as u32).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(format_tag.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(8000u32.to_le_bytes());
+        wav.extend((8000 * bits as u32 / 8).to_le_bytes());
+        wav.extend((bits / 8).to_le_bytes());
+        wav.extend(bits.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend((data.len() as u32).to_le_bytes());
+        wav.extend(data);
+        wav
+    }
+
+    /// Inserts a JUNK chunk before the fmt chunk, as BWF and DAW exports do.
+    fn with_junk(mut wav: Vec<u8>) -> Vec<u8> {
+        let junk = [b"JUNK".as_slice(), &8u32.to_le_bytes(), &[0; 8]].concat();
+        wav.splice(12..12, junk);
+        let len = wav.len() as u32 - 8;
+        wav[4..8].copy_from_slice(&len.to_le_bytes());
+        wav
+    }
+
+    #[test]
+    fn test_wav_formats() -> Result<()> {
+        for (bytes, expected) in [
+            (wav(1, 8, &[0, 128, 255]), vec![-128.0 / 127.0, 0.0, 1.0]),
+            (
+                wav(1, 24, &[0, 0, 0x80, 0xff, 0xff, 0x7f]),
+                vec![-8388608.0 / 8388607.0, 1.0],
+            ),
+            (wav(7, 8, &[0xff, 0x00]), vec![0.0, -32124.0 / 32767.0]),
+            (
+                wav(6, 8, &[0xd5, 0x55]),
+                vec![8.0 / 32767.0, -8.0 / 32767.0],
+            ),
+            (
+                with_junk(wav(1, 16, &[0, 0x80, 0xff, 0x7f])),
+                vec![-32768.0 / 32767.0, 1.0],
+            ),
+            (
+                with_junk(wav(7, 8, &[0xff, 0x00])),
+                vec![0.0, -32124.0 / 32767.0],
+            ),
+        ] {
+            let reader = std::io::Cursor::new(bytes);
+            let (info, samples) = read_audio_from_reader(reader, Some(AudioFormat::Wav), 8000)?;
+            assert_eq!((info.sample_rate, info.channels), (8000, 1));
+            assert_eq!(samples, expected);
+        }
+        let mut no_channels = wav(7, 8, &[0xff, 0x00]);
+        no_channels[22..24].copy_from_slice(&0u16.to_le_bytes());
+        // a chunk claiming 4 GiB that the file doesn't have
+        let mut truncated = with_junk(wav(1, 16, &[0, 0x80]));
+        truncated[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        for bytes in [no_channels, truncated] {
+            let reader = std::io::Cursor::new(bytes);
+            assert!(matches!(
+                read_audio_from_reader(reader, Some(AudioFormat::Wav), 8000),
+                Err(Error::InvalidAudio(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_format() -> Result<()> {
+        for (path, format) in [
+            (INPUT_WAV, AudioFormat::Wav),
+            (INPUT_WEBM, AudioFormat::Matroska),
+            (INPUT_WEBA, AudioFormat::Matroska),
+        ] {
+            let mut file = std::fs::File::open(path)?;
+            assert_eq!(AudioFormat::detect(&mut file)?, Some(format));
+            assert_eq!(std::io::Seek::stream_position(&mut file)?, 0);
+        }
+        assert_eq!(
+            AudioFormat::from_magic(b"OggS\0\x02"),
+            Some(AudioFormat::Ogg)
+        );
+        assert_eq!(AudioFormat::from_magic(b"fLaC"), Some(AudioFormat::Flac));
+        assert_eq!(AudioFormat::from_magic(b"ID3\x04"), Some(AudioFormat::Mp3));
+        assert_eq!(
+            AudioFormat::from_magic(b"\xff\xfb\x90"),
+            Some(AudioFormat::Mp3)
+        );
+        assert_eq!(AudioFormat::from_magic(b"\xff\xf1\x50"), None);
+        assert_eq!(
+            AudioFormat::from_magic(b"\0\0\0\x20ftypM4A "),
+            Some(AudioFormat::Mp4)
+        );
+        assert_eq!(AudioFormat::from_magic(b"RIFF"), None);
+        assert_eq!(AudioFormat::from_extension("opus"), Some(AudioFormat::Ogg));
         Ok(())
     }
 
     #[test]
     #[ignore]
     fn test_transcode() -> Result<()> {
-        let silero = Silero::default()?;
-        silero.transcode_audio(INPUT_WEBM.as_ref(), OUTPUT_WAV.as_ref())
+        let silero = silero()?;
+        silero.transcode_audio(INPUT_WEBM.as_ref(), OUTPUT_WAV.as_ref())?;
+        Ok(())
     }
 }
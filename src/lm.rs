@@ -0,0 +1,104 @@
+use crate::error::ensure;
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+const UNK_LOG10_PROB: f32 = -10.0;
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidLanguageModel(reason)
+}
+
+#[derive(Debug)]
+pub struct LanguageModel {
+    ngrams: Vec<HashMap<String, (f32, f32)>>,
+    unk: f32,
+}
+
+impl LanguageModel {
+    pub fn from_arpa(arpa: &str) -> Result<Self> {
+        let mut ngrams: Vec<HashMap<String, (f32, f32)>> = vec![];
+        let mut order = 0;
+        for line in arpa.lines() {
+            let line = line.trim();
+            if line.is_empty() || line == "\\data\\" || line.starts_with("ngram ") {
+                continue;
+            }
+            if line == "\\end\\" {
+                break;
+            }
+            if let Some(section) = line.strip_prefix('\\') {
+                order = section
+                    .strip_suffix("-grams:")
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| invalid(format!("invalid section {}", line)))?;
+                ensure!(
+                    order == ngrams.len() + 1,
+                    invalid(format!("unexpected section {}", line))
+                );
+                ngrams.push(HashMap::new());
+                continue;
+            }
+            ensure!(
+                order > 0,
+                invalid(format!("entry outside of section {}", line))
+            );
+            let mut fields = line.split_whitespace();
+            let prob = fields
+                .next()
+                .and_then(|prob| prob.parse().ok())
+                .ok_or_else(|| invalid(format!("invalid probability in {}", line)))?;
+            let words: Vec<&str> = fields.by_ref().take(order).collect();
+            ensure!(
+                words.len() == order,
+                invalid(format!("invalid {}-gram {}", order, line))
+            );
+            let backoff = fields
+                .next()
+                .map(str::parse)
+                .transpose()
+                .map_err(|_| invalid(format!("invalid backoff in {}", line)))?
+                .unwrap_or(0.0);
+            ngrams[order - 1].insert(words.join(" "), (prob, backoff));
+        }
+        ensure!(!ngrams.is_empty(), invalid("no n-grams".into()));
+        let unk = ngrams[0]
+            .get("<unk>")
+            .map(|(prob, _)| *prob)
+            .unwrap_or(UNK_LOG10_PROB);
+        Ok(Self { ngrams, unk })
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_arpa(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn order(&self) -> usize {
+        self.ngrams.len()
+    }
+
+    /// Natural log probability of `word` following `context`.
+    pub fn score(&self, context: &[&str], word: &str) -> f32 {
+        let start = context.len().saturating_sub(self.order() - 1);
+        self.log10_prob(&context[start..], word) * std::f32::consts::LN_10
+    }
+
+    fn log10_prob(&self, context: &[&str], word: &str) -> f32 {
+        let mut ngram = context.join(" ");
+        if !ngram.is_empty() {
+            ngram.push(' ');
+        }
+        ngram.push_str(word);
+        if let Some((prob, _)) = self.ngrams[context.len()].get(&ngram) {
+            return *prob;
+        }
+        if context.is_empty() {
+            return self.unk;
+        }
+        let backoff = self.ngrams[context.len() - 1]
+            .get(&context.join(" "))
+            .map(|(_, backoff)| *backoff)
+            .unwrap_or_default();
+        backoff + self.log10_prob(&context[1..], word)
+    }
+}
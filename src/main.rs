@@ -1,6 +1,9 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
-use silero::Silero;
+use silero::{
+    LanguageIdentifier, LanguageRouter, ModelRegistry, OutputFormat, RawFormat, Silero,
+    SileroBuilder, SubtitleOptions, Vad,
+};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -9,13 +12,113 @@ struct Opts {
     input: Vec<PathBuf>,
     #[clap(short, long)]
     output_dir: Option<PathBuf>,
+    /// Read the inputs as headerless audio given as encoding:sample_rate:channels, with
+    /// encoding one of s16le, f32le, mulaw or alaw, e.g. s16le:8000:1.
+    #[clap(long)]
+    raw: Option<RawFormat>,
+    /// Directory with the model files, searched in $SILERO_MODEL_DIR and the XDG data
+    /// directory if unset.
+    #[clap(long)]
+    model_dir: Option<PathBuf>,
+    /// Name or language of the model to use from the model directory, or auto to pick one
+    /// per input by the language identified in it.
+    #[clap(short, long, default_value = "en")]
+    lang: String,
+    /// Language identification model used by --lang auto.
+    #[clap(long)]
+    lang_id_model: Option<PathBuf>,
+    /// JSON list of the languages the language identification model outputs.
+    #[clap(long)]
+    lang_id_languages: Option<PathBuf>,
+    /// Split the audio at silences detected from its energy before transcribing.
+    #[clap(long)]
+    vad: bool,
+    /// Split the audio at silences detected by a Silero VAD model.
+    #[clap(long)]
+    vad_model: Option<PathBuf>,
+    /// Number of windows transcribed in one inference call.
+    #[clap(long, default_value_t = 10)]
+    batch_size: usize,
+    /// Number of threads used to run the model, chosen by onnxruntime if unset.
+    #[clap(long)]
+    threads: Option<usize>,
+    /// Output format, one of txt, srt, vtt or json.
+    #[clap(short, long, default_value = "txt")]
+    format: OutputFormat,
+    /// Maximum number of characters per subtitle line.
+    #[clap(long, default_value_t = 42)]
+    max_line_length: usize,
+    /// Maximum duration of a subtitle cue in seconds.
+    #[clap(long, default_value_t = 7.0)]
+    max_cue_duration: f32,
+}
+
+impl Opts {
+    fn builder(&self) -> SileroBuilder {
+        let mut builder = Silero::builder().batch_size(self.batch_size);
+        if let Some(threads) = self.threads {
+            builder = builder.intra_threads(threads);
+        }
+        builder
+    }
+
+    fn vad(&self) -> Result<Option<Vad>> {
+        Ok(if let Some(model) = self.vad_model.as_ref() {
+            Some(Vad::silero_from_path(model, 0.5)?)
+        } else if self.vad {
+            Some(Vad::energy(-40.0))
+        } else {
+            None
+        })
+    }
 }
 
 fn main() -> Result<()> {
     env_logger::init();
     let opts = Opts::parse();
-    let silero = Silero::default()?;
-    let output_dir = opts.output_dir.unwrap_or_default();
-    silero.stt(&opts.input, &output_dir)?;
+    let output_dir = opts.output_dir.clone().unwrap_or_default();
+    let options = SubtitleOptions {
+        max_line_length: opts.max_line_length,
+        max_cue_duration: opts.max_cue_duration,
+        ..Default::default()
+    };
+    let model_dir = opts
+        .model_dir
+        .clone()
+        .map_or_else(silero::find_model_dir, Ok);
+    if opts.lang == "auto" {
+        let identifier = LanguageIdentifier::from_path(
+            opts.lang_id_model
+                .as_ref()
+                .context("--lang auto requires --lang-id-model")?,
+            opts.lang_id_languages
+                .as_ref()
+                .context("--lang auto requires --lang-id-languages")?,
+        )?;
+        let mut models = ModelRegistry::open(&model_dir?)?.load_languages(|| opts.builder())?;
+        for silero in &mut models {
+            silero.set_vad(opts.vad()?);
+        }
+        let mut router = LanguageRouter::new(identifier, models)?;
+        router.set_raw_format(opts.raw);
+        router.stt(&opts.input, &output_dir, opts.format, &options)?;
+        return Ok(());
+    }
+    let mut silero = match model_dir {
+        Ok(dir) => ModelRegistry::open(&dir)?.load(&opts.lang, opts.builder())?,
+        #[cfg(feature = "embedded-model")]
+        Err(_) => {
+            anyhow::ensure!(
+                opts.lang == "en",
+                "only the embedded English model is available"
+            );
+            opts.builder().build_default()?
+        }
+        #[cfg(not(feature = "embedded-model"))]
+        Err(err) => return Err(err.into()),
+    };
+    silero.set_vad(opts.vad()?);
+    silero.set_raw_format(opts.raw);
+    silero.stt(&opts.input, &output_dir, opts.format, &options)?;
     Ok(())
 }
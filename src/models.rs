@@ -0,0 +1,135 @@
+use crate::{Error, Result};
+use crate::{Silero, SileroBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Environment variable naming a directory with the model files.
+pub const MODEL_DIR_ENV: &str = "SILERO_MODEL_DIR";
+
+/// Describes a model in a model directory, with paths relative to that directory.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub name: String,
+    pub language: String,
+    pub model: PathBuf,
+    pub labels: PathBuf,
+    pub sample_rate: usize,
+    /// Length in seconds of the windows the model is run on.
+    #[serde(default = "default_window")]
+    pub window: f32,
+}
+
+fn default_window() -> f32 {
+    10.8
+}
+
+impl Default for ModelManifest {
+    /// The English model as laid out by `models/en/download.sh`.
+    fn default() -> Self {
+        Self {
+            name: "en_v5".into(),
+            language: "en".into(),
+            model: "en_v5.onnx".into(),
+            labels: "en_v1_labels.json".into(),
+            sample_rate: 16000,
+            window: default_window(),
+        }
+    }
+}
+
+/// The models available in a directory, listed in its `manifest.json` or else the English
+/// model files.
+#[derive(Clone, Debug)]
+pub struct ModelRegistry {
+    dir: PathBuf,
+    models: Vec<ModelManifest>,
+}
+
+impl ModelRegistry {
+    pub fn open(dir: &Path) -> Result<Self> {
+        let manifest = dir.join(MANIFEST_FILE);
+        let models = if manifest.is_file() {
+            let json = std::fs::read_to_string(&manifest)?;
+            serde_json::from_str(&json)
+                .map_err(|err| Error::InvalidModel(format!("{:?}: {}", manifest, err)))?
+        } else {
+            vec![ModelManifest::default()]
+        };
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            models,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn models(&self) -> &[ModelManifest] {
+        &self.models
+    }
+
+    /// Looks a model up by name, or by language picking the first model listed for it.
+    pub fn find(&self, name: &str) -> Option<&ModelManifest> {
+        self.models
+            .iter()
+            .find(|model| model.name == name)
+            .or_else(|| self.models.iter().find(|model| model.language == name))
+    }
+
+    pub fn load(&self, name: &str, builder: SileroBuilder) -> Result<Silero> {
+        let manifest = self.find(name).ok_or_else(|| Error::ModelNotFound {
+            name: name.to_string(),
+            dir: self.dir.clone(),
+            available: self.models.iter().map(|model| model.name.clone()).collect(),
+        })?;
+        builder.build_from_manifest(&self.dir, manifest)
+    }
+
+    /// Loads the first model listed for each language.
+    pub fn load_languages(&self, builder: impl Fn() -> SileroBuilder) -> Result<Vec<Silero>> {
+        let mut languages = vec![];
+        let mut models = vec![];
+        for manifest in &self.models {
+            if !languages.contains(&&manifest.language) {
+                languages.push(&manifest.language);
+                models.push(builder().build_from_manifest(&self.dir, manifest)?);
+            }
+        }
+        Ok(models)
+    }
+}
+
+fn has_model(dir: &Path) -> bool {
+    let manifest = ModelManifest::default();
+    dir.join(MANIFEST_FILE).is_file()
+        || dir.join(manifest.model).is_file() && dir.join(manifest.labels).is_file()
+}
+
+/// Directories searched for the model files, in order: `$SILERO_MODEL_DIR`, then `silero`
+/// in the XDG data directory.
+fn search_path() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(dir) = std::env::var_os(MODEL_DIR_ENV) {
+        dirs.push(PathBuf::from(dir));
+    }
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    if let Some(data_home) = data_home {
+        dirs.push(data_home.join("silero"));
+    }
+    dirs
+}
+
+/// Finds the first directory on the search path with a model manifest or the English model.
+pub fn find_model_dir() -> Result<PathBuf> {
+    let dirs = search_path();
+    match dirs.iter().find(|dir| has_model(dir)) {
+        Some(dir) => Ok(dir.clone()),
+        None => Err(Error::NoModelDir(dirs)),
+    }
+}
@@ -0,0 +1,159 @@
+use crate::transcript::{Transcript, Word};
+use crate::{Error, Result};
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Txt,
+    Srt,
+    Vtt,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Txt => "txt",
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn render(self, transcript: &Transcript, options: &SubtitleOptions) -> Result<String> {
+        Ok(match self {
+            Self::Txt => transcript.text.clone(),
+            Self::Srt => to_srt(transcript, options),
+            Self::Vtt => to_vtt(transcript, options),
+            Self::Json => serde_json::to_string_pretty(transcript)?,
+        })
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "txt" => Self::Txt,
+            "srt" => Self::Srt,
+            "vtt" => Self::Vtt,
+            "json" => Self::Json,
+            _ => return Err(Error::UnsupportedOutputFormat(s.to_string())),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SubtitleOptions {
+    pub max_line_length: usize,
+    pub max_lines: usize,
+    pub max_cue_duration: f32,
+}
+
+impl Default for SubtitleOptions {
+    fn default() -> Self {
+        Self {
+            max_line_length: 42,
+            max_lines: 2,
+            max_cue_duration: 7.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cue {
+    pub start: f32,
+    pub end: f32,
+    pub lines: Vec<String>,
+}
+
+impl Cue {
+    fn new(word: &Word) -> Self {
+        Self {
+            start: word.start,
+            end: word.end,
+            lines: vec![word.text.clone()],
+        }
+    }
+
+    /// Adds the word to the cue unless it would exceed the configured limits.
+    fn push(&mut self, word: &Word, options: &SubtitleOptions) -> bool {
+        if word.end - self.start > options.max_cue_duration {
+            return false;
+        }
+        let line = self.lines.last_mut().unwrap();
+        if line.len() + 1 + word.text.len() <= options.max_line_length {
+            line.push(' ');
+            line.push_str(&word.text);
+        } else if self.lines.len() < options.max_lines {
+            self.lines.push(word.text.clone());
+        } else {
+            return false;
+        }
+        self.end = word.end;
+        true
+    }
+}
+
+pub fn cues(transcript: &Transcript, options: &SubtitleOptions) -> Vec<Cue> {
+    let mut cues = vec![];
+    for segment in &transcript.segments {
+        let mut cue: Option<Cue> = None;
+        for word in &segment.words {
+            if let Some(cue) = cue.as_mut() {
+                if cue.push(word, options) {
+                    continue;
+                }
+            }
+            cues.extend(cue.replace(Cue::new(word)));
+        }
+        cues.extend(cue);
+    }
+    cues
+}
+
+fn timestamp(seconds: f32, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+pub fn to_srt(transcript: &Transcript, options: &SubtitleOptions) -> String {
+    let mut srt = String::new();
+    for (i, cue) in cues(transcript, options).into_iter().enumerate() {
+        writeln!(srt, "{}", i + 1).unwrap();
+        writeln!(
+            srt,
+            "{} --> {}",
+            timestamp(cue.start, ','),
+            timestamp(cue.end, ',')
+        )
+        .unwrap();
+        writeln!(srt, "{}\n", cue.lines.join("\n")).unwrap();
+    }
+    srt
+}
+
+pub fn to_vtt(transcript: &Transcript, options: &SubtitleOptions) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+    for cue in cues(transcript, options) {
+        writeln!(
+            vtt,
+            "{} --> {}",
+            timestamp(cue.start, '.'),
+            timestamp(cue.end, '.')
+        )
+        .unwrap();
+        writeln!(vtt, "{}\n", cue.lines.join("\n")).unwrap();
+    }
+    vtt
+}
@@ -0,0 +1,113 @@
+use crate::transcript::{Segment, Transcript, Word};
+use crate::{Result, Silero};
+use std::collections::vec_deque::Drain;
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamingResult {
+    Partial(Transcript),
+    Final(Transcript),
+}
+
+pub struct StreamingRecognizer<'a> {
+    silero: &'a Silero,
+    buffer: Vec<f32>,
+    offset: usize,
+    pending: usize,
+    step: usize,
+    results: VecDeque<StreamingResult>,
+}
+
+impl<'a> StreamingRecognizer<'a> {
+    pub fn new(silero: &'a Silero) -> Self {
+        Self {
+            silero,
+            buffer: Vec::with_capacity(silero.max_sequence_length),
+            offset: 0,
+            pending: 0,
+            step: silero.sample_rate,
+            results: Default::default(),
+        }
+    }
+
+    pub fn set_step(&mut self, step: usize) {
+        self.step = step.clamp(1, self.silero.max_sequence_length / 2);
+    }
+
+    pub fn push_samples(&mut self, mut samples: &[f32]) -> Result<()> {
+        while !samples.is_empty() {
+            let n = (self.step - self.pending).min(samples.len());
+            self.buffer.extend_from_slice(&samples[..n]);
+            self.pending += n;
+            samples = &samples[n..];
+            if self.pending == self.step {
+                self.pending = 0;
+                self.decode(false)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<()> {
+        self.pending = 0;
+        self.decode(true)
+    }
+
+    pub fn results(&mut self) -> Drain<'_, StreamingResult> {
+        self.results.drain(..)
+    }
+
+    fn decode(&mut self, finish: bool) -> Result<()> {
+        let sample_rate = self.silero.sample_rate as f32;
+        let offset = self.offset as f32 / sample_rate;
+        let mut transcript = self
+            .silero
+            .infer(std::slice::from_ref(&self.buffer))?
+            .pop()
+            .unwrap_or_default();
+        transcript.shift(offset);
+        if finish {
+            self.offset += self.buffer.len();
+            self.buffer.clear();
+            self.results.push_back(StreamingResult::Final(transcript));
+            return Ok(());
+        }
+        if self.buffer.len() + self.step <= self.silero.max_sequence_length {
+            self.results.push_back(StreamingResult::Partial(transcript));
+            return Ok(());
+        }
+        let keep = self.silero.chunk_overlap.min(self.buffer.len());
+        let horizon = offset + (self.buffer.len() - keep) as f32 / sample_rate;
+        // the next window has to fit, so at least this much is cut even if a word straddles it
+        let min_cut = self.buffer.len() + self.step - self.silero.max_sequence_length;
+        let min_end = offset + min_cut as f32 / sample_rate;
+        let (words, end) = final_words(&transcript, horizon, min_end);
+        let cut = (((end - offset) * sample_rate) as usize).clamp(min_cut, self.buffer.len());
+        self.buffer.drain(..cut);
+        self.offset += cut;
+        if !words.is_empty() {
+            let transcript = Transcript::from_segments(vec![Segment::new(offset, end, words)]);
+            self.results.push_back(StreamingResult::Final(transcript));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the words of `transcript` that end before `horizon`, or start before `min_end` and
+/// would be cut otherwise, with the time up to which the audio is finalized. Without such words
+/// the audio is finalized up to the first word, so that it isn't cut in half.
+pub(crate) fn final_words(transcript: &Transcript, horizon: f32, min_end: f32) -> (Vec<Word>, f32) {
+    let words: Vec<Word> = transcript
+        .words()
+        .take_while(|word| word.end <= horizon || word.start < min_end)
+        .cloned()
+        .collect();
+    let end = match words.last() {
+        Some(word) => word.end,
+        None => transcript
+            .words()
+            .next()
+            .map_or(horizon, |word| word.start.min(horizon)),
+    };
+    (words, end.max(min_end))
+}
@@ -0,0 +1,243 @@
+use crate::audio::AudioInfo;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::PathBuf;
+
+const STITCH_TOLERANCE: f32 = 0.5;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub fingerprint: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectedLanguage {
+    pub language: String,
+    pub probability: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    pub path: Option<PathBuf>,
+    #[serde(flatten)]
+    pub info: AudioInfo,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Token {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+    pub confidence: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+    pub confidence: f32,
+    pub tokens: Vec<Token>,
+}
+
+impl Word {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        let text = tokens.iter().map(|token| token.text.as_str()).collect();
+        let start = tokens.first().map(|token| token.start).unwrap_or_default();
+        let end = tokens.last().map(|token| token.end).unwrap_or_default();
+        let confidence = mean(tokens.iter().map(|token| token.confidence));
+        Self {
+            text,
+            start,
+            end,
+            confidence,
+            tokens,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+    pub confidence: f32,
+    pub words: Vec<Word>,
+}
+
+impl Segment {
+    pub fn new(start: f32, end: f32, words: Vec<Word>) -> Self {
+        let text = words
+            .iter()
+            .map(|word| word.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let confidence = mean(words.iter().map(|word| word.confidence));
+        Self {
+            start,
+            end,
+            text,
+            confidence,
+            words,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    pub confidence: f32,
+    pub segments: Vec<Segment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<DetectedLanguage>,
+}
+
+impl Transcript {
+    pub fn new(tokens: Vec<Token>, duration: f32) -> Self {
+        let text = tokens
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let mut words = vec![];
+        let mut word = vec![];
+        for token in tokens {
+            if token.text.trim().is_empty() {
+                if !word.is_empty() {
+                    words.push(Word::new(std::mem::take(&mut word)));
+                }
+            } else {
+                word.push(token);
+            }
+        }
+        if !word.is_empty() {
+            words.push(Word::new(word));
+        }
+        let segment = Segment::new(0.0, duration, words);
+        Self {
+            text,
+            confidence: segment.confidence,
+            segments: vec![segment],
+            ..Default::default()
+        }
+    }
+
+    pub fn from_segments(segments: Vec<Segment>) -> Self {
+        let mut transcript = Self::default();
+        transcript.set_segments(segments);
+        transcript
+    }
+
+    fn set_segments(&mut self, segments: Vec<Segment>) {
+        self.text = segments
+            .iter()
+            .filter(|segment| !segment.text.is_empty())
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.segments = segments;
+        self.confidence = mean(self.words().map(|word| word.confidence));
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = &Word> + '_ {
+        self.segments.iter().flat_map(|segment| &segment.words)
+    }
+
+    pub fn shift(&mut self, offset: f32) {
+        for segment in &mut self.segments {
+            segment.start += offset;
+            segment.end += offset;
+            for word in &mut segment.words {
+                word.start += offset;
+                word.end += offset;
+                for token in &mut word.tokens {
+                    token.start += offset;
+                    token.end += offset;
+                }
+            }
+        }
+    }
+
+    pub fn append(&mut self, next: Transcript) {
+        let mut segments = std::mem::take(&mut self.segments);
+        segments.extend(next.segments);
+        self.set_segments(segments);
+    }
+
+    /// Merges the transcript of the following, overlapping chunk into the last
+    /// segment.
+    pub fn stitch(&mut self, next: Transcript, overlap: Range<f32>) {
+        let mut segments = std::mem::take(&mut self.segments);
+        let mut next = next.segments.into_iter();
+        match (segments.pop(), next.next()) {
+            (Some(a), Some(b)) => {
+                let words = stitch_words(a.words, b.words, overlap);
+                segments.push(Segment::new(a.start, b.end, words));
+            }
+            (a, b) => segments.extend(a.into_iter().chain(b)),
+        }
+        segments.extend(next);
+        self.set_segments(segments);
+    }
+}
+
+/// The words of both chunks in the overlapped region are aligned on a shared word
+/// closest to the middle of the overlap, falling back to cutting at the middle.
+fn stitch_words(mut a: Vec<Word>, b: Vec<Word>, overlap: Range<f32>) -> Vec<Word> {
+    let cut = (overlap.start + overlap.end) / 2.0;
+    let first = a
+        .iter()
+        .position(|word| word.end > overlap.start)
+        .unwrap_or(a.len());
+    let last = b
+        .iter()
+        .position(|word| word.start >= overlap.end)
+        .unwrap_or(b.len());
+    let anchor = (first..a.len())
+        .flat_map(|i| (0..last).map(move |j| (i, j)))
+        .filter(|(i, j)| {
+            a[*i].text == b[*j].text && (a[*i].start - b[*j].start).abs() < STITCH_TOLERANCE
+        })
+        .min_by(|(_, i), (_, j)| {
+            let i = (b[*i].start - cut).abs();
+            let j = (b[*j].start - cut).abs();
+            i.total_cmp(&j)
+        });
+    let (keep, skip) = match anchor {
+        Some(anchor) => anchor,
+        None => {
+            let keep = a
+                .iter()
+                .position(|word| word.start >= cut)
+                .unwrap_or(a.len());
+            let end = a[..keep]
+                .last()
+                .map(|word| word.end)
+                .unwrap_or(overlap.start);
+            let skip = b
+                .iter()
+                .position(|word| word.start >= cut && (word.start + word.end) / 2.0 >= end)
+                .unwrap_or(b.len());
+            (keep, skip)
+        }
+    };
+    a.truncate(keep);
+    a.extend(b.into_iter().skip(skip));
+    a
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, len) = values.fold((0.0, 0), |(sum, len), value| (sum + value, len + 1));
+    if len == 0 {
+        return 0.0;
+    }
+    sum / len as f32
+}
@@ -0,0 +1,130 @@
+use crate::Result;
+use ndarray::{arr0, s, Array2, Array3, ArrayView1};
+use ort::Session;
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Clone, Debug)]
+pub struct VadOptions {
+    pub min_speech_duration: f32,
+    pub min_silence_duration: f32,
+    pub speech_pad: f32,
+}
+
+impl Default for VadOptions {
+    fn default() -> Self {
+        Self {
+            min_speech_duration: 0.25,
+            min_silence_duration: 0.5,
+            speech_pad: 0.2,
+        }
+    }
+}
+
+enum Detector {
+    Energy { threshold_db: f32 },
+    Silero { session: Session, threshold: f32 },
+}
+
+pub struct Vad {
+    detector: Detector,
+    pub options: VadOptions,
+}
+
+impl Vad {
+    pub fn energy(threshold_db: f32) -> Self {
+        Self {
+            detector: Detector::Energy { threshold_db },
+            options: Default::default(),
+        }
+    }
+
+    pub fn silero(model: &[u8], threshold: f32) -> Result<Self> {
+        let session = crate::create_session(model)?;
+        Ok(Self {
+            detector: Detector::Silero { session, threshold },
+            options: Default::default(),
+        })
+    }
+
+    pub fn silero_from_path(model: &Path, threshold: f32) -> Result<Self> {
+        Self::silero(&std::fs::read(model)?, threshold)
+    }
+
+    fn frame_length(sample_rate: usize) -> usize {
+        sample_rate * 32 / 1000
+    }
+
+    fn speech(&self, samples: &[f32], sample_rate: usize) -> Result<Vec<bool>> {
+        let frame_length = Self::frame_length(sample_rate);
+        match &self.detector {
+            Detector::Energy { threshold_db } => Ok(samples
+                .chunks(frame_length)
+                .map(|frame| {
+                    let power = frame.iter().map(|x| x * x).sum::<f32>() / frame.len() as f32;
+                    10.0 * power.max(1e-10).log10() > *threshold_db
+                })
+                .collect()),
+            Detector::Silero { session, threshold } => {
+                let sr = arr0(sample_rate as i64).into_dyn();
+                let mut h = Array3::<f32>::zeros((2, 1, 64)).into_dyn();
+                let mut c = Array3::<f32>::zeros((2, 1, 64)).into_dyn();
+                let mut speech = Vec::with_capacity(samples.len() / frame_length + 1);
+                for frame in samples.chunks(frame_length) {
+                    let mut input = Array2::<f32>::zeros((1, frame_length));
+                    input
+                        .slice_mut(s![0, ..frame.len()])
+                        .assign(&ArrayView1::from(frame));
+                    let input = input.into_dyn();
+                    let (probability, hn, cn) = {
+                        let outputs = session.run(ort::inputs![
+                            "input" => &input.as_standard_layout(),
+                            "sr" => &sr.as_standard_layout(),
+                            "h" => &h.as_standard_layout(),
+                            "c" => &c.as_standard_layout()
+                        ])?;
+                        let probability = outputs["output"].extract_tensor::<f32>()?.view()[[0, 0]];
+                        let hn = outputs["hn"].extract_tensor::<f32>()?.view().to_owned();
+                        let cn = outputs["cn"].extract_tensor::<f32>()?.view().to_owned();
+                        (probability, hn, cn)
+                    };
+                    h = hn;
+                    c = cn;
+                    speech.push(probability > *threshold);
+                }
+                Ok(speech)
+            }
+        }
+    }
+
+    pub fn segments(&self, samples: &[f32], sample_rate: usize) -> Result<Vec<Range<usize>>> {
+        let frame_length = Self::frame_length(sample_rate);
+        let to_samples = |seconds: f32| (seconds * sample_rate as f32) as usize;
+        let min_speech = to_samples(self.options.min_speech_duration);
+        let min_silence = to_samples(self.options.min_silence_duration);
+        let pad = to_samples(self.options.speech_pad);
+        let mut runs: Vec<Range<usize>> = vec![];
+        for (i, speech) in self.speech(samples, sample_rate)?.into_iter().enumerate() {
+            if !speech {
+                continue;
+            }
+            let frame = i * frame_length..((i + 1) * frame_length).min(samples.len());
+            match runs.last_mut() {
+                Some(run) if frame.start - run.end < min_silence => run.end = frame.end,
+                _ => runs.push(frame),
+            }
+        }
+        let mut segments: Vec<Range<usize>> = vec![];
+        for run in runs {
+            if run.len() < min_speech {
+                continue;
+            }
+            let segment = run.start.saturating_sub(pad)..(run.end + pad).min(samples.len());
+            match segments.last_mut() {
+                Some(last) if segment.start <= last.end => last.end = segment.end,
+                _ => segments.push(segment),
+            }
+        }
+        Ok(segments)
+    }
+}